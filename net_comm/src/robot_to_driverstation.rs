@@ -1,4 +1,5 @@
 pub mod error;
+pub mod usage;
 
 use std::borrow::Cow;
 
@@ -8,7 +9,10 @@ use util::{
     team_number::TeamNumber,
 };
 
-use self::error::{Errors, Warnings};
+use self::{
+    error::{Errors, Warnings},
+    usage::{UsageEntry, UsageEntryReadError},
+};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum VersionInfo<'a> {
@@ -93,7 +97,7 @@ pub enum MessageKind<'a> {
     UsageReport {
         team: TeamNumber,
         unknwon: u8,
-        usage: (),
+        usage: Vec<UsageEntry<'a>>,
     },
 }
 
@@ -221,6 +225,16 @@ impl<'a> Message<'a> {
         }
     }
 
+    pub fn usage_report(team: TeamNumber, usage: impl Into<Vec<UsageEntry<'a>>>) -> Self {
+        Self {
+            kind: MessageKind::UsageReport {
+                team,
+                unknwon: 0,
+                usage: usage.into(),
+            },
+        }
+    }
+
     pub fn set_ms(&mut self, time_ms: u32) {
         match &mut self.kind {
             MessageKind::Message { ms, .. }
@@ -260,6 +274,16 @@ pub enum MessageReadError {
     ReportStartValueNonZero,
     InvalidMsgCode(u8),
     InvalidVersionDeviceTag(u8),
+    InvalidResourceType(u8),
+}
+
+impl From<UsageEntryReadError> for MessageReadError {
+    fn from(value: UsageEntryReadError) -> Self {
+        match value {
+            UsageEntryReadError::BufferReaderError(err) => Self::BufferReaderError(err),
+            UsageEntryReadError::InvalidResourceType(val) => Self::InvalidResourceType(val),
+        }
+    }
 }
 
 impl From<BufferReaderError> for MessageReadError {
@@ -292,6 +316,19 @@ impl<'a> CreateFromBuf<'a> for Message<'a> {
                     msg: Cow::Borrowed(buf.read_str(buf.remaining_buf_len())?),
                 },
             },
+            0x01 => Self {
+                kind: MessageKind::UsageReport {
+                    team: TeamNumber(buf.read_u16()?),
+                    unknwon: buf.read_u8()?,
+                    usage: {
+                        let mut usage = Vec::new();
+                        while buf.remaining_buf_len() > 0 {
+                            usage.push(UsageEntry::read(buf)?);
+                        }
+                        usage
+                    },
+                },
+            },
             0x04 => Self {
                 kind: MessageKind::DisableFaults {
                     comms: buf.read_u16()?,
@@ -474,12 +511,14 @@ impl<'a, 'm> WriteToBuff<'a> for Message<'m> {
                     _ => {}
                 }
 
-                buf.write_u16(msg.len() as u16)?;
-                buf.write_buf(msg.as_bytes())?;
-                buf.write_u16(loc.len() as u16)?;
-                buf.write_buf(loc.as_bytes())?;
-                buf.write_u16(stack.len() as u16)?;
-                buf.write_buf(stack.as_bytes())?;
+                for str in [msg, loc, stack] {
+                    let len: u16 = str
+                        .len()
+                        .try_into()
+                        .map_err(|_| BufferWritterError::SizeValueOverflow)?;
+                    buf.write_u16(len)?;
+                    buf.write_buf(str.as_bytes())?;
+                }
             }
             MessageKind::VersionInfo { kind } => {
                 // buf.write_u32(0)?;
@@ -492,14 +531,17 @@ impl<'a, 'm> WriteToBuff<'a> for Message<'m> {
                         buf.write_short_str(kind.get_tag())?;
                         buf.write_short_str(msg)?;
                     }
-                    VersionInfo::CANTalon(idk, can_id)
-                    | VersionInfo::PDP(idk, can_id)
-                    | VersionInfo::PCM(idk, can_id) => {
+                    VersionInfo::CANTalon(idk, can_id) | VersionInfo::PCM(idk, can_id) => {
                         buf.write_u16(*idk)?;
                         buf.write_u8(*can_id)?;
                         //tags and strings
                         buf.write_u16(0)?;
                     }
+                    VersionInfo::PDP(idk, can_id) => {
+                        // the pdp doesn't have the empty tag and string
+                        buf.write_u16(*idk)?;
+                        buf.write_u8(*can_id)?;
+                    }
                     VersionInfo::Empty(str) => {
                         // this shidz emptyyy
                         buf.write(4)?.fill(0);
@@ -537,7 +579,11 @@ impl<'a, 'm> WriteToBuff<'a> for Message<'m> {
                 unknwon,
                 usage,
             } => {
-                todo!("NOT ACTUALLY WORKING YET :3");
+                buf.write_u16(team.0)?;
+                buf.write_u8(*unknwon)?;
+                for entry in usage {
+                    entry.write(buf)?;
+                }
             }
         }
         Ok(())
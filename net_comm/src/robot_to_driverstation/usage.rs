@@ -0,0 +1,308 @@
+use std::borrow::Cow;
+
+use util::{
+    buffer_reader::{BufferReader, BufferReaderError},
+    buffer_writter::{BufferWritter, BufferWritterError},
+};
+
+/// What kind of thing is being reported as used, these match the `kResourceType_*` values from the
+/// WPILib usage reporting header
+#[repr(u8)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ResourceType {
+    kResourceType_Controller,
+    kResourceType_Module,
+    kResourceType_Language,
+    kResourceType_CANPlugin,
+    kResourceType_Accelerometer,
+    kResourceType_ADXL345,
+    kResourceType_AnalogChannel,
+    kResourceType_AnalogTrigger,
+    kResourceType_AnalogTriggerOutput,
+    kResourceType_CANJaguar,
+    kResourceType_Compressor, // 10,
+    kResourceType_Counter,
+    kResourceType_Dashboard,
+    kResourceType_DigitalInput,
+    kResourceType_DigitalOutput,
+    kResourceType_DriverStationCIO,
+    kResourceType_DriverStationEIO,
+    kResourceType_DriverStationLCD,
+    kResourceType_Encoder,
+    kResourceType_GearTooth,
+    kResourceType_Gyro, // 20,
+    kResourceType_I2C,
+    kResourceType_Framework,
+    kResourceType_Jaguar,
+    kResourceType_Joystick,
+    kResourceType_Kinect,
+    kResourceType_KinectStick,
+    kResourceType_PIDController,
+    kResourceType_Preferences,
+    kResourceType_PWM,
+    kResourceType_Relay, // 30,
+    kResourceType_RobotDrive,
+    kResourceType_SerialPort,
+    kResourceType_Servo,
+    kResourceType_Solenoid,
+    kResourceType_SPI,
+    kResourceType_Task,
+    kResourceType_Ultrasonic,
+    kResourceType_Victor,
+    kResourceType_Button,
+    kResourceType_Command, // 40,
+    kResourceType_AxisCamera,
+    kResourceType_PCVideoServer,
+    kResourceType_SmartDashboard,
+    kResourceType_Talon,
+    kResourceType_HiTechnicColorSensor,
+    kResourceType_HiTechnicAccel,
+    kResourceType_HiTechnicCompass,
+    kResourceType_SRF08,
+    kResourceType_AnalogOutput,
+    kResourceType_VictorSP, // 50,
+    kResourceType_PWMTalonSRX,
+    kResourceType_CANTalonSRX,
+    kResourceType_ADXL362,
+    kResourceType_ADXRS450,
+    kResourceType_RevSPARK,
+    kResourceType_MindsensorsSD540,
+    kResourceType_DigitalGlitchFilter,
+    kResourceType_ADIS16448,
+    kResourceType_PDP,
+    kResourceType_PCM, // 60,
+    kResourceType_PigeonIMU,
+    kResourceType_NidecBrushless,
+    kResourceType_CANifier,
+    kResourceType_TalonFX,
+    kResourceType_CTRE_future1,
+    kResourceType_CTRE_future2,
+    kResourceType_CTRE_future3,
+    kResourceType_CTRE_future4,
+    kResourceType_CTRE_future5,
+    kResourceType_CTRE_future6, // 70,
+    kResourceType_LinearFilter,
+    kResourceType_XboxController,
+    kResourceType_UsbCamera,
+    kResourceType_NavX,
+    kResourceType_Pixy,
+    kResourceType_Pixy2,
+    kResourceType_ScanseSweep,
+    kResourceType_Shuffleboard,
+    kResourceType_CAN,
+    kResourceType_DigilentDMC60, // 80,
+    kResourceType_PWMVictorSPX,
+    kResourceType_RevSparkMaxPWM,
+    kResourceType_RevSparkMaxCAN,
+    kResourceType_ADIS16470,
+    kResourceType_PIDController2,
+    kResourceType_ProfiledPIDController,
+    kResourceType_Kinematics,
+    kResourceType_Odometry,
+    kResourceType_Units,
+    kResourceType_TrapezoidProfile, // 90,
+    kResourceType_DutyCycle,
+    kResourceType_AddressableLEDs,
+    kResourceType_FusionVenom,
+    kResourceType_CTRE_future7,
+    kResourceType_CTRE_future8,
+    kResourceType_CTRE_future9,
+    kResourceType_CTRE_future10,
+    kResourceType_CTRE_future11,
+    kResourceType_CTRE_future12,
+    kResourceType_CTRE_future13, // 100,
+    kResourceType_CTRE_future14,
+}
+
+impl ResourceType {
+    pub fn from_u8(val: u8) -> Option<Self> {
+        if val <= Self::kResourceType_CTRE_future14 as u8 {
+            // Safety: the enum is repr(u8) with no gaps from 0 to the last varient
+            Some(unsafe { std::mem::transmute::<u8, ResourceType>(val) })
+        } else {
+            None
+        }
+    }
+}
+
+#[allow(non_upper_case_globals)]
+pub mod instances {
+    pub const kLanguage_LabVIEW: u16 = 1;
+    pub const kLanguage_CPlusPlus: u16 = 2;
+    pub const kLanguage_Java: u16 = 3;
+    pub const kLanguage_Python: u16 = 4;
+    pub const kLanguage_DotNet: u16 = 5;
+    pub const kLanguage_Kotlin: u16 = 6;
+
+    pub const kCANPlugin_BlackJagBridge: u16 = 1;
+    pub const kCANPlugin_2CAN: u16 = 2;
+
+    pub const kFramework_Iterative: u16 = 1;
+    pub const kFramework_Simple: u16 = 2;
+    pub const kFramework_CommandControl: u16 = 3;
+    pub const kFramework_Timed: u16 = 4;
+    pub const kFramework_ROS: u16 = 5;
+    pub const kFramework_RobotBuilder: u16 = 6;
+
+    pub const kRobotDrive_ArcadeStandard: u16 = 1;
+    pub const kRobotDrive_ArcadeButtonSpin: u16 = 2;
+    pub const kRobotDrive_ArcadeRatioCurve: u16 = 3;
+    pub const kRobotDrive_Tank: u16 = 4;
+    pub const kRobotDrive_MecanumPolar: u16 = 5;
+    pub const kRobotDrive_MecanumCartesian: u16 = 6;
+    pub const kRobotDrive2_DifferentialArcade: u16 = 7;
+    pub const kRobotDrive2_DifferentialTank: u16 = 8;
+    pub const kRobotDrive2_DifferentialCurvature: u16 = 9;
+    pub const kRobotDrive2_MecanumCartesian: u16 = 10;
+    pub const kRobotDrive2_MecanumPolar: u16 = 11;
+    pub const kRobotDrive2_KilloughCartesian: u16 = 12;
+    pub const kRobotDrive2_KilloughPolar: u16 = 13;
+
+    pub const kDriverStationCIO_Analog: u16 = 1;
+    pub const kDriverStationCIO_DigitalIn: u16 = 2;
+    pub const kDriverStationCIO_DigitalOut: u16 = 3;
+
+    pub const kDriverStationEIO_Acceleration: u16 = 1;
+    pub const kDriverStationEIO_AnalogIn: u16 = 2;
+    pub const kDriverStationEIO_AnalogOut: u16 = 3;
+    pub const kDriverStationEIO_Button: u16 = 4;
+    pub const kDriverStationEIO_LED: u16 = 5;
+    pub const kDriverStationEIO_DigitalIn: u16 = 6;
+    pub const kDriverStationEIO_DigitalOut: u16 = 7;
+    pub const kDriverStationEIO_FixedDigitalOut: u16 = 8;
+    pub const kDriverStationEIO_PWM: u16 = 9;
+    pub const kDriverStationEIO_Encoder: u16 = 10;
+    pub const kDriverStationEIO_TouchSlider: u16 = 11;
+
+    pub const kADXL345_SPI: u16 = 1;
+    pub const kADXL345_I2C: u16 = 2;
+
+    pub const kCommand_Scheduler: u16 = 1;
+    pub const kCommand2_Scheduler: u16 = 2;
+
+    pub const kSmartDashboard_Instance: u16 = 1;
+
+    pub const kKinematics_DifferentialDrive: u16 = 1;
+    pub const kKinematics_MecanumDrive: u16 = 2;
+    pub const kKinematics_SwerveDrive: u16 = 3;
+
+    pub const kOdometry_DifferentialDrive: u16 = 1;
+    pub const kOdometry_MecanumDrive: u16 = 2;
+    pub const kOdometry_SwerveDrive: u16 = 3;
+}
+
+/// A single `report_usage` entry
+///
+/// Each entry is encoded as the resource type (u8), instance (u16), context (u16)
+/// and a short string for the feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageEntry<'a> {
+    pub resource: ResourceType,
+    pub instance: u16,
+    pub context: u16,
+    pub feature: Cow<'a, str>,
+}
+
+impl<'a> UsageEntry<'a> {
+    pub fn new(
+        resource: ResourceType,
+        instance: u16,
+        context: u16,
+        feature: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        Self {
+            resource,
+            instance,
+            context,
+            feature: feature.into(),
+        }
+    }
+
+    pub fn into_owned(self) -> UsageEntry<'static> {
+        UsageEntry {
+            resource: self.resource,
+            instance: self.instance,
+            context: self.context,
+            feature: Cow::Owned(self.feature.into_owned()),
+        }
+    }
+
+    /// How many bytes this entry takes up in a usage report
+    pub fn encoded_len(&self) -> usize {
+        1 + 2 + 2 + 1 + self.feature.len()
+    }
+
+    pub(super) fn write<'b, T: BufferWritter<'b>>(
+        &self,
+        buf: &mut T,
+    ) -> Result<(), BufferWritterError> {
+        buf.write_u8(self.resource as u8)?;
+        buf.write_u16(self.instance)?;
+        buf.write_u16(self.context)?;
+        buf.write_short_str(&self.feature)
+    }
+
+    pub(super) fn read(buf: &mut BufferReader<'a>) -> Result<Self, UsageEntryReadError> {
+        let resource = buf.read_u8()?;
+        Ok(Self {
+            resource: ResourceType::from_u8(resource)
+                .ok_or(UsageEntryReadError::InvalidResourceType(resource))?,
+            instance: buf.read_u16()?,
+            context: buf.read_u16()?,
+            feature: Cow::Borrowed(buf.read_short_str()?),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum UsageEntryReadError {
+    BufferReaderError(BufferReaderError),
+    InvalidResourceType(u8),
+}
+
+impl From<BufferReaderError> for UsageEntryReadError {
+    fn from(value: BufferReaderError) -> Self {
+        Self::BufferReaderError(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use util::{
+        buffer_reader::{BufferReader, CreateFromBuf},
+        buffer_writter::{BufferWritter, VecBufferWritter, WriteToBuff},
+        team_number::TeamNumber,
+    };
+
+    use super::{instances, ResourceType, UsageEntry};
+    use crate::robot_to_driverstation::{Message, MessageKind};
+
+    #[test]
+    pub fn usage_report_round_trip() {
+        let usage = vec![
+            UsageEntry::new(
+                ResourceType::kResourceType_Language,
+                instances::kLanguage_Java,
+                0,
+                "",
+            ),
+            UsageEntry::new(ResourceType::kResourceType_CTRE_future14, 3, 1, "feature"),
+        ];
+        let mut writter = VecBufferWritter::new();
+        Message::usage_report(TeamNumber(1114), usage.clone())
+            .write_to_buf(&mut writter)
+            .unwrap();
+
+        let message = Message::create_from_buf(&mut BufferReader::new(writter.curr_buf())).unwrap();
+        assert_eq!(
+            message.kind,
+            MessageKind::UsageReport {
+                team: TeamNumber(1114),
+                unknwon: 0,
+                usage
+            }
+        );
+        assert_eq!(ResourceType::from_u8(102), None);
+    }
+}
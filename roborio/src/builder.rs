@@ -0,0 +1,208 @@
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    sync::atomic::AtomicU32,
+};
+
+use util::team_number::TeamNumber;
+
+use crate::{
+    can_stats::CanStatsConfig, input::InputEventQueueConfig, stdio::StdioCaptureConfig,
+    tcp::TcpRoleFilters, telemetry::SystemTelemetryConfig, RoborioCom, TcpConnectionRole,
+    TcpFrameFilter, TcpQueuePolicy,
+};
+
+/// Where the daemon binds its sockets and where it sends its udp responses
+///
+/// The defaults are what the offical driverstation expects from a roborio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoborioComAddrs {
+    pub bind_ip: IpAddr,
+    pub udp_receive_port: u16,
+    pub udp_send_port: u16,
+    pub tcp_port: u16,
+}
+
+impl Default for RoborioComAddrs {
+    fn default() -> Self {
+        Self {
+            bind_ip: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            udp_receive_port: 1110,
+            udp_send_port: 1150,
+            tcp_port: 1740,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RoborioComBuilder {
+    addrs: RoborioComAddrs,
+    team_number: Option<TeamNumber>,
+    advertise_mdns: bool,
+    connection_disable_timeout_ms: u32,
+    connection_reset_timeout_ms: u32,
+    tcp_queue_capacity: usize,
+    tcp_queue_policy: TcpQueuePolicy,
+    tcp_role_filters: TcpRoleFilters,
+    system_telemetry: Option<SystemTelemetryConfig>,
+    can_stats: Option<CanStatsConfig>,
+    stdio_capture: Option<StdioCaptureConfig>,
+    input_event_queue: Option<InputEventQueueConfig>,
+    sync_system_clock: bool,
+}
+
+impl Default for RoborioComBuilder {
+    fn default() -> Self {
+        Self {
+            addrs: Default::default(),
+            team_number: None,
+            advertise_mdns: true,
+            connection_disable_timeout_ms: 120,
+            connection_reset_timeout_ms: 20000,
+            tcp_queue_capacity: 0x20000,
+            tcp_queue_policy: TcpQueuePolicy::DropOldest,
+            tcp_role_filters: Default::default(),
+            system_telemetry: None,
+            can_stats: None,
+            stdio_capture: None,
+            input_event_queue: None,
+            sync_system_clock: false,
+        }
+    }
+}
+
+impl RoborioComBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ip our udp and tcp sockets bind to, use `127.0.0.1` to keep everything on loopback
+    pub fn bind_ip(mut self, bind_ip: impl Into<IpAddr>) -> Self {
+        self.addrs.bind_ip = bind_ip.into();
+        self
+    }
+
+    /// The port we receive driverstation udp packets on (1110 by default)
+    pub fn udp_receive_port(mut self, port: u16) -> Self {
+        self.addrs.udp_receive_port = port;
+        self
+    }
+
+    /// The port on the driverstation we send our udp responses to (1150 by default)
+    pub fn udp_send_port(mut self, port: u16) -> Self {
+        self.addrs.udp_send_port = port;
+        self
+    }
+
+    /// The port we listen for driverstation tcp connections on (1740 by default)
+    pub fn tcp_port(mut self, port: u16) -> Self {
+        self.addrs.tcp_port = port;
+        self
+    }
+
+    /// The team number we report to the driverstation (in usage reports) and advertise over mDNS
+    pub fn team_number(mut self, team_number: impl Into<TeamNumber>) -> Self {
+        self.team_number = Some(team_number.into());
+        self
+    }
+
+    /// Advertise `roboRIO-<team>-FRC._ni-rt._tcp.local.` while the daemon runs (on by default),
+    /// nothing is advertised without a team number
+    pub fn advertise_mdns(mut self, advertise: bool) -> Self {
+        self.advertise_mdns = advertise;
+        self
+    }
+
+    /// See [`RoborioCom::set_udp_connection_disable_timeout`]
+    pub fn connection_disable_timeout_ms(mut self, timeout_ms: u32) -> Self {
+        self.connection_disable_timeout_ms = timeout_ms;
+        self
+    }
+
+    /// See [`RoborioCom::set_udp_connection_timeout`]
+    pub fn connection_reset_timeout_ms(mut self, timeout_ms: u32) -> Self {
+        self.connection_reset_timeout_ms = timeout_ms;
+        self
+    }
+
+    /// How many bytes each tcp connection can have waiting to be sent before the
+    /// [`TcpQueuePolicy`] kicks in (128KiB by default)
+    pub fn tcp_queue_capacity(mut self, bytes: usize) -> Self {
+        self.tcp_queue_capacity = bytes;
+        self
+    }
+
+    /// What to do when a tcp connections queue is full ([`TcpQueuePolicy::DropOldest`] by default)
+    pub fn tcp_queue_policy(mut self, policy: TcpQueuePolicy) -> Self {
+        self.tcp_queue_policy = policy;
+        self
+    }
+
+    /// See [`RoborioCom::set_tcp_role_filter`]
+    pub fn tcp_role_filter(mut self, role: TcpConnectionRole, filter: TcpFrameFilter) -> Self {
+        *self.tcp_role_filters.get_mut(role) = filter;
+        self
+    }
+
+    /// Fill the cpu, ram and disk usage tags from the system while the daemon runs (off by default),
+    /// see [`RoborioCom::set_system_telemetry`]
+    pub fn system_telemetry(mut self, config: SystemTelemetryConfig) -> Self {
+        self.system_telemetry = Some(config);
+        self
+    }
+
+    /// Fill the can usage tag from a SocketCAN interface while the daemon runs (off by default),
+    /// see [`RoborioCom::set_can_stats`]
+    pub fn can_stats(mut self, config: CanStatsConfig) -> Self {
+        self.can_stats = Some(config);
+        self
+    }
+
+    /// Send everything the process prints to stdout/stderr to the driverstation console while the
+    /// daemon runs (off by default), see [`RoborioCom::set_stdio_capture`]
+    pub fn capture_stdio(mut self, config: StdioCaptureConfig) -> Self {
+        self.stdio_capture = Some(config);
+        self
+    }
+
+    /// Queue up controller input events as packets come in (off by default), see
+    /// [`RoborioCom::set_input_event_queue`]
+    pub fn input_event_queue(mut self, config: InputEventQueueConfig) -> Self {
+        self.input_event_queue = Some(config);
+        self
+    }
+
+    /// Set the system clock to the driverstations time whenever it sends it (off by default), see
+    /// [`RoborioCom::set_sync_system_clock`]
+    pub fn sync_system_clock(mut self, sync: bool) -> Self {
+        self.sync_system_clock = sync;
+        self
+    }
+
+    pub fn build(self) -> RoborioCom {
+        let mut com = RoborioCom::default();
+        com.common.addrs = self.addrs;
+        com.common.team_number = self.team_number;
+        com.common.advertise_mdns = self.advertise_mdns;
+        com.udp.connection_disable_timeout_ms = AtomicU32::new(self.connection_disable_timeout_ms);
+        com.udp.connection_reset_timeout_ms = AtomicU32::new(self.connection_reset_timeout_ms);
+        com.tcp.queue_capacity = self.tcp_queue_capacity;
+        com.tcp.queue_policy = self.tcp_queue_policy;
+        *com.tcp.role_filters.lock() = self.tcp_role_filters;
+        com.set_system_telemetry(self.system_telemetry);
+        com.set_can_stats(self.can_stats);
+        com.set_stdio_capture(self.stdio_capture);
+        com.set_input_event_queue(self.input_event_queue);
+        com.set_sync_system_clock(self.sync_system_clock);
+        com
+    }
+}
+
+impl RoborioCom {
+    pub fn builder() -> RoborioComBuilder {
+        RoborioComBuilder::new()
+    }
+
+    pub fn get_addrs(&self) -> RoborioComAddrs {
+        self.common.addrs
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    collections::VecDeque,
+    io,
+    ops::Deref,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use robot_comm::robot_to_driver::RobotToDriverCanUsage;
+
+use crate::{collector::PeriodicCollector, PossibleRcSelf, RoborioCom, RoborioComError};
+
+/// Bits an extended (29 bit id) frame takes up on the bus without any data, including the interframe space
+///
+/// Bit stuffing is ignored so the utilization is a slight underestimate
+const FRAME_OVERHEAD_BITS: u64 = 67;
+
+/// Which SocketCAN interface the can stats collector reads and how
+///
+/// The defaults read `can0` at the 1Mbit/s the FRC CAN bus runs at. What goes in the can usage tag:
+/// - `utilization`, `rx` and `tx` (the error counts) are over the last [`CanStatsConfig::window`]
+/// - `bus_off` and `tx_full` (frames the kernel dropped because its transmit queue was full) count
+///   up from when the collector started
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanStatsConfig {
+    /// The directory the interface directories are in, normally `/sys/class/net`
+    pub sysfs_root: PathBuf,
+    pub interface: String,
+    /// Bits per second the bus runs at, sysfs doesn't expose this
+    pub bitrate: u32,
+    /// How often the counters are read
+    pub interval: Duration,
+    /// Utilization and error counts are over roughly this much time
+    pub window: Duration,
+}
+
+impl Default for CanStatsConfig {
+    fn default() -> Self {
+        Self {
+            sysfs_root: PathBuf::from("/sys/class/net"),
+            interface: "can0".to_owned(),
+            bitrate: 1_000_000,
+            interval: Duration::from_millis(100),
+            window: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The counters we care about out of `statistics/`, all of them only ever count up
+#[derive(Debug, Default, Clone, Copy)]
+struct CanCounters {
+    packets: u64,
+    bytes: u64,
+    rx_errors: u64,
+    tx_errors: u64,
+    tx_dropped: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct CanStats {
+    config: CanStatsConfig,
+    samples: VecDeque<(Instant, CanCounters)>,
+    /// the kernel turns the carrier off when the controller goes bus off
+    carrier: bool,
+    bus_off: u32,
+    /// the kernels lifetime count when we started so `tx_full` counts from then like `bus_off`
+    tx_dropped_start: Option<u64>,
+}
+
+impl CanStats {
+    pub(crate) fn new(config: CanStatsConfig) -> Self {
+        Self {
+            config,
+            samples: VecDeque::new(),
+            carrier: true,
+            bus_off: 0,
+            tx_dropped_start: None,
+        }
+    }
+
+    fn read(&self, file: &str) -> io::Result<String> {
+        let path = self
+            .config
+            .sysfs_root
+            .join(&self.config.interface)
+            .join(file);
+        Ok(std::fs::read_to_string(path)?.trim().to_owned())
+    }
+
+    fn counter(&self, name: &str) -> io::Result<u64> {
+        self.read(&format!("statistics/{name}"))?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn counters(&self) -> io::Result<CanCounters> {
+        Ok(CanCounters {
+            packets: self.counter("rx_packets")? + self.counter("tx_packets")?,
+            bytes: self.counter("rx_bytes")? + self.counter("tx_bytes")?,
+            rx_errors: self.counter("rx_errors")?,
+            tx_errors: self.counter("tx_errors")?,
+            tx_dropped: self.counter("tx_dropped")?,
+        })
+    }
+
+    /// Read the interface and work out the usage over the window ending at `now`
+    pub(crate) fn sample(&mut self, now: Instant) -> io::Result<RobotToDriverCanUsage> {
+        let counters = self.counters()?;
+
+        // a missing carrier file just means the driver doesn't report it
+        let carrier = self.read("carrier").map_or(true, |carrier| carrier != "0");
+        if self.carrier && !carrier {
+            self.bus_off = self.bus_off.saturating_add(1);
+        }
+        self.carrier = carrier;
+
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|(time, _)| now.saturating_duration_since(*time) >= self.config.window)
+        {
+            self.samples.pop_front();
+        }
+        let (start, first) = self.samples.front().copied().unwrap_or((now, counters));
+        self.samples.push_back((now, counters));
+
+        let elapsed = now.saturating_duration_since(start).as_secs_f64();
+        let frames = counters.packets.saturating_sub(first.packets);
+        let bits = frames * FRAME_OVERHEAD_BITS + counters.bytes.saturating_sub(first.bytes) * 8;
+        let utilization = if elapsed > 0.0 && self.config.bitrate > 0 {
+            (bits as f64 / (elapsed * self.config.bitrate as f64)).min(1.0) as f32
+        } else {
+            0.0
+        };
+
+        let tx_dropped_start = *self.tx_dropped_start.get_or_insert(counters.tx_dropped);
+        let errors = |now: u64, first: u64| now.saturating_sub(first).min(u8::MAX as u64) as u8;
+        Ok(RobotToDriverCanUsage {
+            utilization,
+            bus_off: self.bus_off,
+            tx_full: counters
+                .tx_dropped
+                .saturating_sub(tx_dropped_start)
+                .min(u32::MAX as u64) as u32,
+            rx: errors(counters.rx_errors, first.rx_errors),
+            tx: errors(counters.tx_errors, first.tx_errors),
+        })
+    }
+}
+
+impl PeriodicCollector for CanStats {
+    type Config = CanStatsConfig;
+
+    fn from_config(config: CanStatsConfig) -> Self {
+        Self::new(config)
+    }
+
+    fn config(&self) -> &CanStatsConfig {
+        &self.config
+    }
+
+    fn interval(&self) -> Duration {
+        self.config.interval
+    }
+
+    fn collect(&mut self, com: &RoborioCom) {
+        com.collect_can_stats(self)
+    }
+}
+
+impl RoborioCom {
+    /// Start (or with `None` stop) filling the can usage tag from a SocketCAN interface
+    ///
+    /// This only does anything while the daemon is running. The collector overwrites anything set with
+    /// [`RoborioCom::set_can_usage`], after it stops the last value it read is kept
+    pub fn set_can_stats(&self, config: Option<CanStatsConfig>) {
+        *self.common.can_stats.lock() = config;
+    }
+
+    pub fn get_can_stats(&self) -> Option<CanStatsConfig> {
+        self.common.can_stats.lock().clone()
+    }
+
+    pub(super) fn run_can_stats_daemon<T: PossibleRcSelf + Deref<Target = Self>>(myself: &T) {
+        Self::run_collector_daemon::<CanStats, _>(myself, |com| com.common.can_stats.lock().clone())
+    }
+
+    pub(crate) fn collect_can_stats(&self, stats: &mut CanStats) {
+        match stats.sample(Instant::now()) {
+            Ok(usage) => self.set_can_usage(Some(usage)),
+            Err(err) => self.report_error(RoborioComError::CanStatsError(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        path::Path,
+        time::{Duration, Instant},
+    };
+
+    use robot_comm::robot_to_driver::RobotToDriverCanUsage;
+
+    use super::{CanStats, CanStatsConfig};
+    use crate::test::TempPath;
+
+    fn write_stats(
+        interface: &Path,
+        packets: u64,
+        bytes: u64,
+        errors: u64,
+        dropped: u64,
+        carrier: bool,
+    ) {
+        let stats = interface.join("statistics");
+        std::fs::create_dir_all(&stats).unwrap();
+        for (name, val) in [
+            ("rx_packets", packets),
+            ("tx_packets", 0),
+            ("rx_bytes", bytes),
+            ("tx_bytes", 0),
+            ("rx_errors", errors),
+            ("tx_errors", 0),
+            ("tx_dropped", dropped),
+        ] {
+            std::fs::write(stats.join(name), format!("{val}\n")).unwrap();
+        }
+        std::fs::write(
+            interface.join("carrier"),
+            if carrier { "1\n" } else { "0\n" },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    pub fn reads_sysfs_fixtures() {
+        let root = TempPath::new("can-stats");
+        let interface = root.join("vcan0");
+        write_stats(&interface, 1000, 8000, 0, 2, true);
+
+        let mut stats = CanStats::new(CanStatsConfig {
+            sysfs_root: root.to_path_buf(),
+            interface: "vcan0".to_owned(),
+            ..Default::default()
+        });
+
+        let start = Instant::now();
+        assert_eq!(
+            stats.sample(start).unwrap(),
+            RobotToDriverCanUsage {
+                utilization: 0.0,
+                bus_off: 0,
+                tx_full: 0,
+                rx: 0,
+                tx: 0,
+            }
+        );
+
+        // 1000 8 byte frames are 131,000 bits, in half a second thats 262 kbit/s
+        write_stats(&interface, 2000, 16000, 3, 5, false);
+        let usage = stats.sample(start + Duration::from_millis(500)).unwrap();
+        assert!((usage.utilization - 0.262).abs() < 0.001);
+        assert_eq!(usage.bus_off, 1);
+        assert_eq!(usage.tx_full, 3);
+        assert_eq!(usage.rx, 3);
+
+        // the first sample has left the window
+        write_stats(&interface, 2000, 16000, 3, 5, true);
+        let usage = stats.sample(start + Duration::from_millis(1500)).unwrap();
+        assert_eq!(usage.utilization, 0.0);
+        assert_eq!(usage.bus_off, 1);
+        assert_eq!(usage.tx_full, 3);
+        assert_eq!(usage.rx, 0);
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    ops::Deref,
+    time::{Duration, Instant},
+};
+
+use crate::{PossibleRcSelf, RoborioCom};
+
+/// How often a collector thread checks if it should stop or if its config changed
+const POLL: Duration = Duration::from_millis(200);
+
+/// Something the daemon samples every so often while it has a config, like the system telemetry or can stats
+pub(crate) trait PeriodicCollector {
+    type Config: Clone + PartialEq;
+
+    fn from_config(config: Self::Config) -> Self;
+
+    fn config(&self) -> &Self::Config;
+
+    /// How long to wait between samples
+    fn interval(&self) -> Duration;
+
+    fn collect(&mut self, com: &RoborioCom);
+}
+
+impl RoborioCom {
+    /// Run `C` every [`PeriodicCollector::interval`] for as long as the daemon runs, `config` is checked
+    /// often so the collector is started, stopped or recreated soon after the config changes
+    pub(crate) fn run_collector_daemon<
+        C: PeriodicCollector,
+        T: PossibleRcSelf + Deref<Target = Self>,
+    >(
+        myself: &T,
+        config: impl Fn(&Self) -> Option<C::Config>,
+    ) {
+        let mut collector: Option<C> = None;
+        let mut next_sample = Instant::now();
+
+        while myself.exists_elsewhere() {
+            let wait = match config(myself) {
+                None => {
+                    collector = None;
+                    POLL
+                }
+                Some(config) => {
+                    let collector = match &mut collector {
+                        Some(collector) if *collector.config() == config => collector,
+                        collector => {
+                            next_sample = Instant::now();
+                            collector.insert(C::from_config(config))
+                        }
+                    };
+                    if Instant::now() >= next_sample {
+                        next_sample = Instant::now() + collector.interval();
+                        collector.collect(myself);
+                    }
+                    next_sample
+                        .saturating_duration_since(Instant::now())
+                        .min(POLL)
+                }
+            };
+            std::thread::sleep(wait);
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use std::{
+    ops::Deref,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::{ControllerInfo, RoborioCom, Warnings};
+
+/// Scale a raw axis to `[-1, 1]`, the driverstation sends `-128..=127` so each half is scaled on its own
+pub fn normalize_axis(raw: i8) -> f32 {
+    if raw < 0 {
+        raw as f32 / 128.0
+    } else {
+        raw as f32 / 127.0
+    }
+}
+
+/// Zero anything within `deadband` of the center and rescale the rest so it still goes smoothly from 0 to 1
+pub fn apply_deadband(value: f32, deadband: f32) -> f32 {
+    if value.abs() <= deadband {
+        0.0
+    } else {
+        (value - deadband.copysign(value)) / (1.0 - deadband)
+    }
+}
+
+/// What kind of controller a view expects to be plugged into its port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExpectedController {
+    Any,
+    Xbox,
+    PS4,
+}
+
+impl ExpectedController {
+    /// If the descriptor the driverstation sent looks like this kind of controller
+    pub fn matches(self, info: &ControllerInfo) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Xbox => info.is_xbox,
+            // the driverstation has no idea what a ps4 controller is, its just a hid device with enough axes and buttons
+            Self::PS4 => !info.is_xbox && info.axis.len() >= 6 && info.buttons >= 14,
+        }
+    }
+}
+
+/// A view over one of the driverstations controller ports with normalized axes
+///
+/// `T` is anything that gets to a [`RoborioCom`], like `&RoborioCom` or `Arc<RoborioCom>`. Axes, buttons and
+/// povs are indexed from 0 like [`RoborioCom::get_axis`] and read as released when the controller isn't plugged in
+#[derive(Debug)]
+pub struct GenericHID<T: Deref<Target = RoborioCom>> {
+    com: T,
+    port: u8,
+    deadband: f32,
+    expected: ExpectedController,
+    /// set once we warned about the descriptor not matching so we only do it once untill it matches again
+    warned: AtomicBool,
+    /// the [`RoborioCom::controller_info_generation`] the descriptor was last checked at, so reads only
+    /// check it again after it changed
+    checked: AtomicUsize,
+}
+
+impl<T: Deref<Target = RoborioCom>> GenericHID<T> {
+    /// A view with no deadband that accepts any controller
+    pub fn new(com: T, port: u8) -> Self {
+        Self {
+            com,
+            port,
+            deadband: 0.0,
+            expected: ExpectedController::Any,
+            warned: AtomicBool::new(false),
+            checked: AtomicUsize::new(usize::MAX),
+        }
+    }
+
+    pub fn with_deadband(mut self, deadband: f32) -> Self {
+        self.set_deadband(deadband);
+        self
+    }
+
+    /// Warn on the driverstation when the controller plugged into this port doesn't look like `expected`
+    pub fn expecting(mut self, expected: ExpectedController) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    /// Applied to every axis read through [`GenericHID::axis`], clamped to `[0, 1]`
+    pub fn set_deadband(&mut self, deadband: f32) {
+        self.deadband = deadband.clamp(0.0, 1.0);
+    }
+
+    pub fn get_deadband(&self) -> f32 {
+        self.deadband
+    }
+
+    pub fn port(&self) -> u8 {
+        self.port
+    }
+
+    pub fn com(&self) -> &RoborioCom {
+        &self.com
+    }
+
+    /// If the driverstation is sending values for this port
+    pub fn is_connected(&self) -> bool {
+        self.com.get_joystick(self.port as usize).is_some()
+    }
+
+    /// The descriptor the driverstation sent for this port
+    pub fn info(&self) -> Option<ControllerInfo> {
+        self.com.get_controller_info(self.port)
+    }
+
+    /// Check the descriptor against the expected kind of controller, warning on the driverstation the first time
+    /// it doesn't match. `true` when it matches or no descriptor was sent yet
+    pub fn check_descriptor(&self) -> bool {
+        if self.expected == ExpectedController::Any {
+            return true;
+        }
+        self.checked
+            .store(self.com.controller_info_generation(), Ordering::Relaxed);
+        // look at it under the lock instead of cloning it, the warning is only built if it'll be sent
+        let mismatch = self.com.with_controller_info(self.port, |info| match info {
+            Some(info) if !self.expected.matches(info) => {
+                Some((!self.warned.load(Ordering::Relaxed)).then(|| {
+                    format!(
+                        "controller {} is a {:?} \"{}\" but a {:?} controller was expected",
+                        self.port, info.js_type, info.name, self.expected
+                    )
+                }))
+            }
+            _ => None,
+        });
+        let Some(warning) = mismatch else {
+            self.warned.store(false, Ordering::Relaxed);
+            return true;
+        };
+        if let Some(warning) = warning {
+            if !self.warned.swap(true, Ordering::Relaxed) {
+                self.com
+                    .send_warning(Warnings::BadJoystickIndex, &warning, "", "");
+            }
+        }
+        false
+    }
+
+    /// [`GenericHID::check_descriptor`] but only if a descriptor changed since it was last checked
+    fn check_descriptor_if_changed(&self) {
+        if self.expected != ExpectedController::Any
+            && self.checked.load(Ordering::Relaxed) != self.com.controller_info_generation()
+        {
+            self.check_descriptor();
+        }
+    }
+
+    /// The axis scaled to `[-1, 1]` without the deadband
+    pub fn raw_axis(&self, axis: u8) -> Option<f32> {
+        self.check_descriptor_if_changed();
+        self.com
+            .get_axis(self.port as usize, axis)
+            .map(normalize_axis)
+    }
+
+    /// The axis scaled to `[-1, 1]` with the deadband applied, 0 if it doesn't exist
+    pub fn axis(&self, axis: u8) -> f32 {
+        apply_deadband(self.raw_axis(axis).unwrap_or(0.0), self.deadband)
+    }
+
+    pub fn button(&self, button: u8) -> bool {
+        self.check_descriptor_if_changed();
+        self.com
+            .get_button(self.port as usize, button)
+            .unwrap_or(false)
+    }
+
+    /// The angle of the pov in degrees clockwise from up, `None` if it isn't pressed
+    pub fn pov(&self, pov: u8) -> Option<u16> {
+        self.check_descriptor_if_changed();
+        self.com.get_pov(self.port as usize, pov)?.get()
+    }
+}
+
+/// The axes of an xbox controller as the driverstation sends them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum XboxAxis {
+    LeftX = 0,
+    LeftY = 1,
+    LeftTrigger = 2,
+    RightTrigger = 3,
+    RightX = 4,
+    RightY = 5,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum XboxButton {
+    A = 0,
+    B = 1,
+    X = 2,
+    Y = 3,
+    LeftBumper = 4,
+    RightBumper = 5,
+    Back = 6,
+    Start = 7,
+    LeftStick = 8,
+    RightStick = 9,
+}
+
+/// A [`GenericHID`] that expects an xbox controller
+#[derive(Debug)]
+pub struct XboxController<T: Deref<Target = RoborioCom>> {
+    hid: GenericHID<T>,
+}
+
+impl<T: Deref<Target = RoborioCom>> XboxController<T> {
+    pub fn new(com: T, port: u8) -> Self {
+        Self {
+            hid: GenericHID::new(com, port).expecting(ExpectedController::Xbox),
+        }
+    }
+
+    pub fn with_deadband(self, deadband: f32) -> Self {
+        Self {
+            hid: self.hid.with_deadband(deadband),
+        }
+    }
+
+    /// The untyped view for anything not covered here
+    pub fn hid(&self) -> &GenericHID<T> {
+        &self.hid
+    }
+
+    pub fn hid_mut(&mut self) -> &mut GenericHID<T> {
+        &mut self.hid
+    }
+
+    pub fn axis(&self, axis: XboxAxis) -> f32 {
+        self.hid.axis(axis as u8)
+    }
+
+    pub fn button(&self, button: XboxButton) -> bool {
+        self.hid.button(button as u8)
+    }
+
+    pub fn left_x(&self) -> f32 {
+        self.axis(XboxAxis::LeftX)
+    }
+
+    pub fn left_y(&self) -> f32 {
+        self.axis(XboxAxis::LeftY)
+    }
+
+    pub fn right_x(&self) -> f32 {
+        self.axis(XboxAxis::RightX)
+    }
+
+    pub fn right_y(&self) -> f32 {
+        self.axis(XboxAxis::RightY)
+    }
+
+    /// From 0 released to 1 fully pressed
+    pub fn left_trigger(&self) -> f32 {
+        self.axis(XboxAxis::LeftTrigger)
+    }
+
+    /// From 0 released to 1 fully pressed
+    pub fn right_trigger(&self) -> f32 {
+        self.axis(XboxAxis::RightTrigger)
+    }
+
+    /// The angle of the dpad in degrees clockwise from up, `None` if it isn't pressed
+    pub fn dpad(&self) -> Option<u16> {
+        self.hid.pov(0)
+    }
+}
+
+/// The axes of a ps4 controller as the driverstation sends them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum PS4Axis {
+    LeftX = 0,
+    LeftY = 1,
+    RightX = 2,
+    L2 = 3,
+    R2 = 4,
+    RightY = 5,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum PS4Button {
+    Square = 0,
+    Cross = 1,
+    Circle = 2,
+    Triangle = 3,
+    L1 = 4,
+    R1 = 5,
+    L2 = 6,
+    R2 = 7,
+    Share = 8,
+    Options = 9,
+    L3 = 10,
+    R3 = 11,
+    PS = 12,
+    Touchpad = 13,
+}
+
+/// A [`GenericHID`] that expects a ps4 controller
+#[derive(Debug)]
+pub struct PS4Controller<T: Deref<Target = RoborioCom>> {
+    hid: GenericHID<T>,
+}
+
+impl<T: Deref<Target = RoborioCom>> PS4Controller<T> {
+    pub fn new(com: T, port: u8) -> Self {
+        Self {
+            hid: GenericHID::new(com, port).expecting(ExpectedController::PS4),
+        }
+    }
+
+    pub fn with_deadband(self, deadband: f32) -> Self {
+        Self {
+            hid: self.hid.with_deadband(deadband),
+        }
+    }
+
+    /// The untyped view for anything not covered here
+    pub fn hid(&self) -> &GenericHID<T> {
+        &self.hid
+    }
+
+    pub fn hid_mut(&mut self) -> &mut GenericHID<T> {
+        &mut self.hid
+    }
+
+    pub fn axis(&self, axis: PS4Axis) -> f32 {
+        self.hid.axis(axis as u8)
+    }
+
+    pub fn button(&self, button: PS4Button) -> bool {
+        self.hid.button(button as u8)
+    }
+
+    pub fn left_x(&self) -> f32 {
+        self.axis(PS4Axis::LeftX)
+    }
+
+    pub fn left_y(&self) -> f32 {
+        self.axis(PS4Axis::LeftY)
+    }
+
+    pub fn right_x(&self) -> f32 {
+        self.axis(PS4Axis::RightX)
+    }
+
+    pub fn right_y(&self) -> f32 {
+        self.axis(PS4Axis::RightY)
+    }
+
+    /// From -1 released to 1 fully pressed
+    pub fn l2_axis(&self) -> f32 {
+        self.axis(PS4Axis::L2)
+    }
+
+    /// From -1 released to 1 fully pressed
+    pub fn r2_axis(&self) -> f32 {
+        self.axis(PS4Axis::R2)
+    }
+
+    /// The angle of the dpad in degrees clockwise from up, `None` if it isn't pressed
+    pub fn dpad(&self) -> Option<u16> {
+        self.hid.pov(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use net_comm::robot_to_driverstation::{error::Warnings, MessageKind};
+    use util::buffer_reader::BufferReader;
+
+    use super::{apply_deadband, normalize_axis, PS4Controller, XboxController};
+    use crate::{tcp::test::take_message, RoborioCom};
+
+    #[test]
+    pub fn normalizes_and_checks_descriptors() {
+        assert_eq!(normalize_axis(-128), -1.0);
+        assert_eq!(normalize_axis(127), 1.0);
+        assert_eq!(normalize_axis(0), 0.0);
+        assert_eq!(apply_deadband(0.05, 0.1), 0.0);
+        assert_eq!(apply_deadband(1.0, 0.1), 1.0);
+        assert!((apply_deadband(-0.55, 0.1) + 0.5).abs() < 1e-6);
+
+        let com = RoborioCom::default();
+        // a descriptor for port 0: not xbox, hid gamepad, 6 axes, 14 buttons, 1 pov
+        let mut frame = vec![0x02, 0, 0, 21, 19];
+        frame.extend_from_slice(b"Wireless Controller");
+        frame.extend_from_slice(&[6, 0, 1, 2, 0, 0, 1, 14, 1]);
+        com.read_data(BufferReader::new(&frame)).unwrap();
+
+        assert!(PS4Controller::new(&com, 0).hid().check_descriptor());
+
+        let xbox = XboxController::new(&com, 0).with_deadband(0.1);
+        // nothing plugged in reads as centered
+        assert_eq!(xbox.left_x(), 0.0);
+        assert!(!xbox.button(super::XboxButton::A));
+        com.send_message("marker");
+
+        let mut buf = vec![0u8; u16::MAX as usize + 2];
+        match take_message(&com, &mut buf) {
+            MessageKind::Warning { warn, msg, .. } => {
+                assert_eq!(warn, Warnings::BadJoystickIndex);
+                assert!(msg.contains("Wireless Controller"));
+            }
+            other => panic!("unexpected message {other:?}"),
+        }
+        // only warned once
+        assert!(matches!(
+            take_message(&com, &mut buf),
+            MessageKind::Message { msg, .. } if msg == "marker"
+        ));
+
+        // reads only look at the descriptor again once it changed, unplugging it resets the warning
+        com.clear_controller_info();
+        assert_eq!(xbox.left_x(), 0.0);
+        com.read_data(BufferReader::new(&frame)).unwrap();
+        assert_eq!(xbox.left_x(), 0.0);
+        assert!(matches!(
+            take_message(&com, &mut buf),
+            MessageKind::Warning {
+                warn: Warnings::BadJoystickIndex,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    net::IpAddr,
+    sync::{mpsc, Arc},
+};
+
+use robot_comm::common::control_code::ControlCode;
+
+use crate::{ControllerInfo, MatchInfo, RoborioCom, RoborioComError};
+
+/// Everything that happens inside the daemon that something else might care about
+///
+/// These are sent to every receiver returned by [`RoborioCom::subscribe`]
+#[derive(Debug, Clone)]
+pub enum RoborioEvent {
+    /// The control code the driverstation sent us changed (mode/enabled/estop/fms etc)
+    ModeChanged {
+        old: ControlCode,
+        new: ControlCode,
+    },
+    DriverstationConnected(IpAddr),
+    DriverstationDisconnected(IpAddr),
+    Estop,
+    RestartCodeRequested,
+    RestartRioRequested,
+    GameData(String),
+    MatchInfo(MatchInfo),
+    /// A controller descriptor was added/changed for the controller at `index`, `None` when its removed
+    /// because the driverstations tcp connection dropped
+    ControllerInfo {
+        index: usize,
+        info: Option<ControllerInfo>,
+    },
+    Error(Arc<RoborioComError>),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Subscribers {
+    senders: spin::Mutex<Vec<mpsc::Sender<RoborioEvent>>>,
+}
+
+impl RoborioCom {
+    /// Get a receiver of every [`RoborioEvent`] from now on
+    ///
+    /// Each subscriber gets its own copy of every event. The channel is unbounded so events will pile up
+    /// if the receiver is never read, dropping the receiver unsubscribes it
+    pub fn subscribe(&self) -> mpsc::Receiver<RoborioEvent> {
+        let (send, recv) = mpsc::channel();
+        self.common.subscribers.senders.lock().push(send);
+        recv
+    }
+
+    pub(crate) fn emit_event(&self, event: RoborioEvent) {
+        let mut senders = self.common.subscribers.senders.lock();
+        if senders.is_empty() {
+            return;
+        }
+        // senders whos receivers have been dropped fail to send so we just get rid of them
+        senders.retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{net::Ipv4Addr, time::Instant};
+
+    use robot_comm::common::request_code::RobotRequestCode;
+
+    use util::buffer_reader::BufferReader;
+
+    use crate::{ControllerDescriptorHandler, RoborioCom, RoborioComError, TcpTagHandler};
+
+    use super::RoborioEvent;
+
+    #[test]
+    pub fn every_subscriber_gets_events() {
+        let com = RoborioCom::default();
+        let first = com.subscribe();
+        let second = com.subscribe();
+        let dropped = com.subscribe();
+        drop(dropped);
+
+        com.report_error(RoborioComError::UdpConnectionTimeoutError);
+
+        for recv in [&first, &second] {
+            assert!(matches!(
+                recv.try_recv().unwrap(),
+                RoborioEvent::Error(err) if matches!(*err, RoborioComError::UdpConnectionTimeoutError)
+            ));
+        }
+        assert_eq!(com.common.subscribers.senders.lock().len(), 2);
+    }
+
+    #[test]
+    pub fn restarts_and_bad_packets_arent_repeated() {
+        let com = RoborioCom::default();
+        *com.common.driverstation_ip.lock() = Some(Ipv4Addr::LOCALHOST.into());
+        let events = com.subscribe();
+        let mut send_buf = [0u8; 1024];
+        let restart = RobotRequestCode::new()
+            .set_restart_roborio_code(true)
+            .to_bits();
+
+        // the driverstation holds the restart request for a few packets
+        for (sequence, request) in [(1, 0), (2, restart), (3, restart), (4, restart), (5, 0)] {
+            let packet = [0, sequence, 1, 0, request, 0];
+            assert!(com.handle_udp_datagram(&packet, Instant::now(), &mut send_buf, None));
+        }
+        // a bad comm version
+        assert!(!com.handle_udp_datagram(&[0, 6, 2, 0, 0, 0], Instant::now(), &mut send_buf, None));
+        assert!(com.handle_udp_datagram(&[0, 7, 1, 0, 0, 0], Instant::now(), &mut send_buf, None));
+
+        let events = events.try_iter().collect::<Vec<_>>();
+        assert!(matches!(
+            events.as_slice(),
+            [
+                RoborioEvent::DriverstationConnected(_),
+                RoborioEvent::RestartCodeRequested,
+                RoborioEvent::Error(_),
+            ]
+        ));
+    }
+
+    #[test]
+    pub fn controller_info_is_removed() {
+        let com = RoborioCom::default();
+        // a gamepad in slot 2 with no axes, 10 buttons and a pov
+        ControllerDescriptorHandler
+            .handle(
+                &com,
+                2,
+                BufferReader::new(&[2, 1, 1, 3, b'p', b'a', b'd', 0, 10, 1]),
+            )
+            .unwrap();
+        assert!(com.get_controller_info(2).is_some());
+        let events = com.subscribe();
+
+        com.clear_controller_info();
+        assert!(com.get_controller_info(2).is_none());
+        assert!(matches!(
+            events.try_iter().collect::<Vec<_>>().as_slice(),
+            [RoborioEvent::ControllerInfo {
+                index: 2,
+                info: None
+            }]
+        ));
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::VecDeque;
+
+use crate::{controller::normalize_axis, Joystick, RoborioCom};
+
+/// Which side of the threshold an axis is on, see [`InputEventQueueConfig::axis_threshold`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisZone {
+    Negative,
+    Center,
+    Positive,
+}
+
+impl AxisZone {
+    fn of(value: f32, threshold: f32) -> Self {
+        if value >= threshold {
+            Self::Positive
+        } else if value <= -threshold {
+            Self::Negative
+        } else {
+            Self::Center
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEventKind {
+    ButtonDown(u8),
+    ButtonUp(u8),
+    /// Angles in degrees clockwise from up, `None` when the pov isn't pressed
+    PovChanged {
+        pov: u8,
+        old: Option<u16>,
+        new: Option<u16>,
+    },
+    /// The axis moved into a different [`AxisZone`], `value` is the new value scaled to `[-1, 1]`
+    AxisCrossed {
+        axis: u8,
+        old: AxisZone,
+        new: AxisZone,
+        value: f32,
+    },
+}
+
+/// Something that changed on a controller between two driverstation packets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputEvent {
+    /// The sequence number of the packet the change showed up in
+    pub sequence: u16,
+    pub controller: u8,
+    pub kind: InputEventKind,
+}
+
+/// Settings for the queue of [`InputEvent`]s, see [`RoborioCom::set_input_event_queue`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputEventQueueConfig {
+    /// The most events kept, the oldest are dropped to make room for new ones
+    pub capacity: usize,
+    /// How far from the center (scaled to `[0, 1]`) an axis has to move to cross into [`AxisZone::Positive`]
+    /// or [`AxisZone::Negative`]
+    pub axis_threshold: f32,
+}
+
+impl Default for InputEventQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 128,
+            axis_threshold: 0.5,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct InputEventQueue {
+    config: InputEventQueueConfig,
+    events: VecDeque<InputEvent>,
+    dropped: usize,
+}
+
+impl InputEventQueue {
+    fn push(&mut self, event: InputEvent) {
+        if self.config.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.events.len() >= self.config.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+}
+
+/// Edges and events worked out as each packet comes in so nothing shorter than a robot loop gets missed
+#[derive(Debug, Default)]
+pub(crate) struct InputTracker {
+    /// a bit for each button that went down since it was last polled
+    pressed: [u32; 6],
+    /// a bit for each button that went up since it was last polled
+    released: [u32; 6],
+    queue: Option<InputEventQueue>,
+}
+
+fn held_buttons(joystick: Option<&Joystick>) -> u32 {
+    let Some(joystick) = joystick else {
+        return 0;
+    };
+    (0..joystick.buttons_len())
+        .filter(|button| joystick.get_button(*button) == Some(true))
+        .fold(0, |held, button| held | 1 << button)
+}
+
+impl InputTracker {
+    /// Compare the joystick in slot `controller` from the last packet to the one that just came in,
+    /// a missing joystick counts as nothing being held
+    pub(crate) fn record(
+        &mut self,
+        controller: usize,
+        sequence: u16,
+        old: Option<&Joystick>,
+        new: Option<&Joystick>,
+    ) {
+        let old_held = held_buttons(old);
+        let new_held = held_buttons(new);
+        let down = new_held & !old_held;
+        let up = old_held & !new_held;
+        if let Some(pressed) = self.pressed.get_mut(controller) {
+            *pressed |= down;
+        }
+        if let Some(released) = self.released.get_mut(controller) {
+            *released |= up;
+        }
+
+        let Some(queue) = &mut self.queue else {
+            return;
+        };
+        let threshold = queue.config.axis_threshold;
+        let mut push = |kind| {
+            queue.push(InputEvent {
+                sequence,
+                controller: controller as u8,
+                kind,
+            })
+        };
+
+        for button in 0..32 {
+            if down >> button & 1 == 1 {
+                push(InputEventKind::ButtonDown(button));
+            } else if up >> button & 1 == 1 {
+                push(InputEventKind::ButtonUp(button));
+            }
+        }
+
+        let povs = |joystick: Option<&Joystick>| joystick.map_or(0, Joystick::povs_len);
+        for pov in 0..povs(old).max(povs(new)) {
+            let angle = |joystick: Option<&Joystick>| joystick.and_then(|j| j.get_pov(pov)?.get());
+            let (old, new) = (angle(old), angle(new));
+            if old != new {
+                push(InputEventKind::PovChanged { pov, old, new });
+            }
+        }
+
+        let axes = |joystick: Option<&Joystick>| joystick.map_or(0, Joystick::axis_len);
+        for axis in 0..axes(old).max(axes(new)) {
+            let value = |joystick: Option<&Joystick>| {
+                joystick
+                    .and_then(|j| j.get_axis(axis))
+                    .map_or(0.0, normalize_axis)
+            };
+            let value_new = value(new);
+            let (old, new) = (
+                AxisZone::of(value(old), threshold),
+                AxisZone::of(value_new, threshold),
+            );
+            if old != new {
+                push(InputEventKind::AxisCrossed {
+                    axis,
+                    old,
+                    new,
+                    value: value_new,
+                });
+            }
+        }
+    }
+
+    /// Forget every edge, used when the driverstation reconnects
+    pub(crate) fn reset(&mut self) {
+        self.pressed = [0; 6];
+        self.released = [0; 6];
+        if let Some(queue) = &mut self.queue {
+            queue.events.clear();
+        }
+    }
+}
+
+fn take_bit(bits: &mut [u32; 6], controller: usize, button: u8) -> bool {
+    let (Some(bits), true) = (bits.get_mut(controller), button < 32) else {
+        return false;
+    };
+    let set = *bits >> button & 1 == 1;
+    *bits &= !(1 << button);
+    set
+}
+
+impl RoborioCom {
+    /// If `button` went down at any point since the last time this was called for it, even if its been
+    /// released again since. Buttons are indexed from 0 like [`RoborioCom::get_button`]
+    pub fn button_pressed_since_last_poll(&self, controller: usize, button: u8) -> bool {
+        take_bit(&mut self.udp.input.lock().pressed, controller, button)
+    }
+
+    /// If `button` went up at any point since the last time this was called for it, even if its been
+    /// pressed again since
+    pub fn button_released_since_last_poll(&self, controller: usize, button: u8) -> bool {
+        take_bit(&mut self.udp.input.lock().released, controller, button)
+    }
+
+    /// Keep a queue of [`InputEvent`]s worked out as each packet comes in (off by default), `None` turns it off.
+    /// Changing this throws away any events still queued
+    pub fn set_input_event_queue(&self, config: Option<InputEventQueueConfig>) {
+        self.udp.input.lock().queue = config.map(|config| InputEventQueue {
+            config,
+            events: VecDeque::new(),
+            dropped: 0,
+        });
+    }
+
+    pub fn get_input_event_queue(&self) -> Option<InputEventQueueConfig> {
+        self.udp
+            .input
+            .lock()
+            .queue
+            .as_ref()
+            .map(|queue| queue.config)
+    }
+
+    /// The oldest queued event
+    pub fn next_input_event(&self) -> Option<InputEvent> {
+        self.udp.input.lock().queue.as_mut()?.events.pop_front()
+    }
+
+    /// Every queued event, oldest first
+    pub fn take_input_events(&self) -> Vec<InputEvent> {
+        match self.udp.input.lock().queue.as_mut() {
+            Some(queue) => queue.events.drain(..).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// How many events were dropped because the queue was full
+    pub fn get_input_events_dropped(&self) -> usize {
+        self.udp
+            .input
+            .lock()
+            .queue
+            .as_ref()
+            .map_or(0, |queue| queue.dropped)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use robot_comm::common::joystick::{Joystick, NonNegU16};
+
+    use super::{AxisZone, InputEvent, InputEventKind, InputEventQueueConfig};
+    use crate::RoborioCom;
+
+    fn joystick(buttons: &[bool], axis: i8, pov: Option<u16>) -> Joystick {
+        let mut joystick = Joystick::new();
+        for button in buttons {
+            joystick.push_button(*button).unwrap();
+        }
+        joystick.push_axis(axis).unwrap();
+        joystick
+            .push_pov(pov.map_or_else(NonNegU16::none, NonNegU16::new))
+            .unwrap();
+        joystick
+    }
+
+    #[test]
+    pub fn tracks_edges_and_events() {
+        let com = RoborioCom::default();
+        com.set_input_event_queue(Some(InputEventQueueConfig {
+            capacity: 4,
+            ..Default::default()
+        }));
+
+        let idle = joystick(&[false, false], 0, None);
+        let tapped = joystick(&[true, false], 127, Some(90));
+        {
+            let mut input = com.udp.input.lock();
+            input.record(1, 10, Some(&idle), Some(&tapped));
+            // released again before anyone polled
+            input.record(1, 11, Some(&tapped), Some(&idle));
+        }
+
+        assert!(com.button_pressed_since_last_poll(1, 0));
+        assert!(!com.button_pressed_since_last_poll(1, 0));
+        assert!(com.button_released_since_last_poll(1, 0));
+        assert!(!com.button_pressed_since_last_poll(1, 1));
+        assert!(!com.button_pressed_since_last_poll(0, 0));
+
+        // 6 events went into a queue of 4 so the 2 oldest are gone
+        assert_eq!(com.get_input_events_dropped(), 2);
+        assert_eq!(
+            com.next_input_event(),
+            Some(InputEvent {
+                sequence: 10,
+                controller: 1,
+                kind: InputEventKind::AxisCrossed {
+                    axis: 0,
+                    old: AxisZone::Center,
+                    new: AxisZone::Positive,
+                    value: 1.0
+                },
+            })
+        );
+        let events = com.take_input_events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].sequence, 11);
+        assert_eq!(events[0].kind, InputEventKind::ButtonUp(0));
+        assert_eq!(
+            events[1].kind,
+            InputEventKind::PovChanged {
+                pov: 0,
+                old: Some(90),
+                new: None
+            }
+        );
+        assert_eq!(
+            events[2].kind,
+            InputEventKind::AxisCrossed {
+                axis: 0,
+                old: AxisZone::Positive,
+                new: AxisZone::Center,
+                value: 0.0
+            }
+        );
+    }
+}
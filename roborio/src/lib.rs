@@ -2,18 +2,59 @@ use std::{
     net::IpAddr,
     ops::Deref,
     panic::{RefUnwindSafe, UnwindSafe},
-    sync::{atomic::AtomicBool, Arc},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Instant,
 };
 
+use builder::RoborioComAddrs;
+use can_stats::CanStatsConfig;
+use event::Subscribers;
+use motor_safety::MotorSafetyRegistry;
+use recording::Recorder;
 use robot_comm::common::error::RobotPacketParseError;
 use spin::{Mutex, RwLock};
+use stdio::StdioCaptureConfig;
 use tcp::RoborioTcp;
+use telemetry::SystemTelemetryConfig;
 use udp::RoborioUdp;
-use util::{buffer_reader::BufferReaderError, buffer_writter::BufferWritterError};
+use usage::UsageReports;
+use util::{
+    buffer_reader::BufferReaderError, buffer_writter::BufferWritterError, team_number::TeamNumber,
+};
 
+pub mod builder;
+pub mod can_stats;
+mod collector;
+pub mod controller;
+pub mod event;
+pub mod input;
+pub mod link_stats;
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub mod logging;
+pub mod match_timer;
+pub mod mdns;
+pub mod motor_safety;
+mod panic_hook;
+pub mod recording;
 pub mod ringbuffer;
+pub mod robot;
+pub mod stdio;
 mod tcp;
+pub mod telemetry;
 mod udp;
+pub mod usage;
+pub mod wall_clock;
+
+pub use net_comm::robot_to_driverstation::error::{Errors, Warnings};
+pub use tcp::{
+    AxisType, ControllerDescriptorHandler, ControllerInfo, GameDataHandler, JoystickType,
+    MatchInfo, MatchInfoHandler, MatchType, TcpConnectionRole, TcpConnectionStats, TcpFrameFilter,
+    TcpQueuePolicy, TcpTagHandler,
+};
 
 pub type Joystick = robot_comm::common::joystick::Joystick;
 
@@ -34,22 +75,67 @@ pub enum RoborioComError {
     UdpCorePacketReadError(RobotPacketParseError),
     UdpPacketTagReadError(RobotPacketParseError),
     UdpConnectionTimeoutError,
-    ModeSwitchHookPanic(Box<dyn std::any::Any + Send>),
+    /// A mode switch hook panicked, this holds the panic message. Use [`RoborioCom::install_panic_hook`]
+    /// to also get it (with where it happened) on the driverstation
+    ModeSwitchHookPanic(String),
+    /// The stop callback of a motor safety actuator panicked, this holds its name and the panic message
+    MotorSafetyStopPanic(String, String),
     //tcp
     TcpIoInitError(std::io::Error),
     TcpIoSendError(std::io::Error),
     TcpIoReceiveError(std::io::Error),
     TcpIoGeneralError(std::io::Error),
     TcpPacketReadError(BufferReaderError),
+    TcpMessageWriteError(BufferWritterError),
+    /// A frame (this many bytes) was too big to ever fit in the u16 frame size
+    TcpFrameTooLarge(usize),
+    /// The shared send buffer no longer starts at a frame so everything in it was thrown out
+    TcpSendBufferCorrupted,
+    /// The driverstation sent a controller descriptor for a controller we dont have
+    TcpInvalidControllerIndex(u8),
+    /// The driverstation sent a frame with a tag we have no [`TcpTagHandler`] for
+    TcpUnknownTag(u8),
+    //telemetry
+    /// Reading the cpu, ram or disk usage for the system telemetry collector failed
+    SystemTelemetryError(std::io::Error),
+    /// Reading the SocketCAN interface for the can stats collector failed
+    CanStatsError(std::io::Error),
+    //mdns
+    /// Starting the mDNS advertisement failed, the robot wont be found by its team number untill
+    /// it is tried again a few seconds later
+    MdnsError(mdns_sd::Error),
+    //recording
+    /// Writing to the recording failed and it was stopped, or it can't keep up and packets are being
+    /// dropped (see [`RoborioCom::get_recording_dropped_packets`])
+    RecordingError(std::io::Error),
+    //stdio
+    /// Redirecting or reading stdout/stderr for the driverstation console failed, they are put back
+    /// untill the capture config changes
+    StdioCaptureError(std::io::Error),
+    //wall clock
+    /// Setting the system clock to the driverstations time failed, usually because we dont have `CAP_SYS_TIME`
+    SystemClockError(std::io::Error),
 }
 
 type ErrorHandler =
-    Box<dyn Fn(&RoborioCom, RoborioComError) + Send + Sync + UnwindSafe + RefUnwindSafe + 'static>;
+    Box<dyn Fn(&RoborioCom, &RoborioComError) + Send + Sync + UnwindSafe + RefUnwindSafe + 'static>;
 
 struct RoborioCommon {
     request_info: AtomicBool,
     error_handler: RwLock<ErrorHandler>,
     driverstation_ip: Mutex<Option<IpAddr>>,
+    addrs: RoborioComAddrs,
+    subscribers: Subscribers,
+    /// message timestamps are relative to this
+    created: Instant,
+    team_number: Option<TeamNumber>,
+    advertise_mdns: bool,
+    usage: Mutex<UsageReports>,
+    motor_safety: Mutex<MotorSafetyRegistry>,
+    telemetry: Mutex<Option<SystemTelemetryConfig>>,
+    can_stats: Mutex<Option<CanStatsConfig>>,
+    stdio_capture: Mutex<Option<StdioCaptureConfig>>,
+    recorder: std::sync::Mutex<Option<Recorder>>,
 }
 
 impl UnwindSafe for RoborioCommon {}
@@ -59,19 +145,31 @@ impl std::fmt::Debug for RoborioCommon {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RoborioCommon")
             .field("request_info", &self.request_info)
+            .field("addrs", &self.addrs)
             .finish()
     }
 }
 
 impl Default for RoborioCommon {
     fn default() -> Self {
-        fn default_error_handler(_com: &RoborioCom, err: RoborioComError) {
+        fn default_error_handler(_com: &RoborioCom, err: &RoborioComError) {
             eprintln!("{:#?}", err)
         }
         Self {
             request_info: Default::default(),
             error_handler: RwLock::new(Box::new(default_error_handler)),
             driverstation_ip: Default::default(),
+            addrs: Default::default(),
+            subscribers: Default::default(),
+            created: Instant::now(),
+            team_number: None,
+            advertise_mdns: true,
+            usage: Default::default(),
+            motor_safety: Default::default(),
+            telemetry: Default::default(),
+            can_stats: Default::default(),
+            stdio_capture: Default::default(),
+            recorder: Default::default(),
         }
     }
 }
@@ -81,30 +179,53 @@ impl RoborioCom {
         T: 'static + Clone + Sync + Send + PossibleRcSelf + Deref<Target = Self>,
     >(
         myself: T,
-    ) {
+    ) -> DaemonHandle {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let myself = DaemonRef {
+            inner: myself,
+            shutdown: shutdown.clone(),
+        };
         // ya so this is weird i promis it makes sense
         // the PossiblyRcSelf will keep the threads alive if we clone it (possibly)
         // so we pass them as a reference instead
-        std::thread::spawn(move || {
+        let thread = std::thread::spawn(move || {
             let myself = &myself;
             std::thread::scope(move |scope| {
                 scope.spawn(|| {
                     Self::run_udp_daemon(myself);
                 });
+                scope.spawn(|| {
+                    Self::run_telemetry_daemon(myself);
+                });
+                scope.spawn(|| {
+                    Self::run_can_stats_daemon(myself);
+                });
+                scope.spawn(|| {
+                    Self::run_mdns_daemon(myself);
+                });
+                scope.spawn(|| {
+                    Self::run_stdio_daemon(myself);
+                });
                 Self::run_tcp_daemon(myself)
             });
         });
+        DaemonHandle {
+            shutdown,
+            thread: Some(thread),
+        }
     }
 
     fn report_error(&self, err: RoborioComError) {
-        self.common.error_handler.read()(self, err)
+        let err = Arc::new(err);
+        self.common.error_handler.read()(self, &err);
+        self.emit_event(event::RoborioEvent::Error(err));
     }
 }
 
 impl RoborioCom {
     pub fn set_error_handler(
         &self,
-        handler: impl Fn(&RoborioCom, RoborioComError)
+        handler: impl Fn(&RoborioCom, &RoborioComError)
             + Send
             + Sync
             + UnwindSafe
@@ -118,11 +239,93 @@ impl RoborioCom {
         func
     }
 
+    pub fn get_team_number(&self) -> Option<TeamNumber> {
+        self.common.team_number
+    }
+
     pub fn get_driverstation_ip(&self) -> Option<IpAddr> {
         *self.common.driverstation_ip.lock()
     }
 }
 
+/// Get the message out of a panic payload, panics with a `&str` or `String` are the only ones we can read
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_owned()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "Box<dyn Any>".to_owned()
+    }
+}
+
+/// Handle to the threads started by [`RoborioCom::start_daemon`]
+///
+/// Dropping the handle does not stop the daemon, it keeps running untill [`DaemonHandle::shutdown`]
+/// is called or every other reference to the [`RoborioCom`] is dropped
+#[derive(Debug)]
+pub struct DaemonHandle {
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DaemonHandle {
+    /// Tell every daemon thread to stop, this doesn't wait for them to actually exit
+    ///
+    /// Each loop wakes up at least every couple hundred ms (or the udp disable timeout if its larger)
+    /// so they will close their sockets shortly after
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::Relaxed)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_none_or(|thread| thread.is_finished())
+    }
+
+    /// Wait for every daemon thread to exit, once this returns all sockets are closed and the ports are free
+    ///
+    /// This will block forever if nothing ever stops the daemon, so usually [`DaemonHandle::shutdown`] should be called first
+    pub fn join(mut self) -> std::thread::Result<()> {
+        match self.thread.take() {
+            Some(thread) => thread.join(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn shutdown_and_join(self) -> std::thread::Result<()> {
+        self.shutdown();
+        self.join()
+    }
+}
+
+/// What the daemon threads actually hold, it lets the [`DaemonHandle`] stop them without needing the
+/// original `PossibleRcSelf` to go away
+#[derive(Clone)]
+struct DaemonRef<T> {
+    inner: T,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl<T: PossibleRcSelf> PossibleRcSelf for DaemonRef<T> {
+    fn exists_elsewhere(&self) -> bool {
+        !self.shutdown.load(Ordering::Relaxed) && self.inner.exists_elsewhere()
+    }
+}
+
+impl<T: Deref<Target = RoborioCom>> Deref for DaemonRef<T> {
+    type Target = RoborioCom;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
 pub trait PossibleRcSelf {
     fn exists_elsewhere(&self) -> bool;
 }
@@ -138,3 +341,89 @@ impl<T> PossibleRcSelf for Arc<T> {
         std::sync::Arc::strong_count(self) > 1
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{
+        net::{Ipv4Addr, TcpListener, UdpSocket},
+        ops::Deref,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use crate::RoborioCom;
+
+    /// Held by any test that replaces the process wide panic hook
+    pub(crate) static PANIC_HOOK: Mutex<()> = Mutex::new(());
+
+    /// A path in the temp dir for test fixtures, whatever ends up there is removed when this drops
+    /// (even if the test panics)
+    pub(crate) struct TempPath(PathBuf);
+
+    impl TempPath {
+        pub(crate) fn new(name: &str) -> Self {
+            Self(std::env::temp_dir().join(format!("roborio-{name}-{}", std::process::id())))
+        }
+    }
+
+    impl Deref for TempPath {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for TempPath {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = if self.0.is_dir() {
+                std::fs::remove_dir_all(&self.0)
+            } else {
+                std::fs::remove_file(&self.0)
+            };
+        }
+    }
+
+    /// Ports nothing else is using right now, the sockets are dropped so the daemon can take them
+    fn free_ports() -> (u16, u16, u16) {
+        let udp_receive = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let udp_send = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let tcp = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        (
+            udp_receive.local_addr().unwrap().port(),
+            udp_send.local_addr().unwrap().port(),
+            tcp.local_addr().unwrap().port(),
+        )
+    }
+
+    #[test]
+    pub fn daemon_shutdown_releases_ports() {
+        let (udp_receive_port, udp_send_port, tcp_port) = free_ports();
+        for _ in 0..3 {
+            let com = Arc::new(
+                RoborioCom::builder()
+                    .bind_ip(Ipv4Addr::LOCALHOST)
+                    .udp_receive_port(udp_receive_port)
+                    .udp_send_port(udp_send_port)
+                    .tcp_port(tcp_port)
+                    .build(),
+            );
+            let daemon = RoborioCom::start_daemon(com.clone());
+            std::thread::sleep(Duration::from_millis(50));
+
+            let start = Instant::now();
+            daemon.shutdown_and_join().unwrap();
+            assert!(start.elapsed() < Duration::from_secs(2));
+
+            UdpSocket::bind((Ipv4Addr::LOCALHOST, udp_receive_port)).unwrap();
+            TcpListener::bind((Ipv4Addr::LOCALHOST, tcp_port)).unwrap();
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::RoborioCom;
+
+/// Upper bounds of the [`JitterHistogram`] buckets, anything above the last one goes in the final bucket
+pub const JITTER_BUCKETS: [Duration; 6] = [
+    Duration::from_millis(1),
+    Duration::from_millis(2),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(20),
+    Duration::from_millis(50),
+];
+
+/// How long we remember individual packets for, the longest window we report on
+const WINDOW: Duration = Duration::from_secs(10);
+const SHORT_WINDOW: Duration = Duration::from_secs(1);
+
+/// How much the time between packets changed from one packet to the next
+///
+/// The driverstation sends a packet every 20ms so a perfect link puts everything in the first bucket
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JitterHistogram {
+    /// `counts[i]` is the number of packets whose jitter was at most `JITTER_BUCKETS[i]`
+    /// (and more than the bucket before it), the last entry counts everything larger
+    pub counts: [usize; JITTER_BUCKETS.len() + 1],
+}
+
+impl JitterHistogram {
+    fn record(&mut self, jitter: Duration) {
+        let bucket = JITTER_BUCKETS
+            .iter()
+            .position(|limit| jitter <= *limit)
+            .unwrap_or(JITTER_BUCKETS.len());
+        self.counts[bucket] += 1;
+    }
+
+    /// The upper bound of each bucket (`None` for the last one) alongside its count
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, usize)> + '_ {
+        JITTER_BUCKETS
+            .iter()
+            .copied()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .zip(self.counts.iter().copied())
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+/// A snapshot of how well the udp link with the driverstation is doing, see [`RoborioCom::link_stats`]
+///
+/// Everything is reset when the connection is
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LinkStats {
+    /// Percentage (0-100) of packets we expected from the driverstation in the last second that never arrived
+    pub packet_loss_1s: f32,
+    /// Same as `packet_loss_1s` but over the last 10 seconds
+    pub packet_loss_10s: f32,
+    pub jitter: JitterHistogram,
+    /// The longest time between two packets from the driverstation since we connected
+    pub longest_gap: Duration,
+    /// Packets that arrived with an older sequence than one we already got
+    pub reordered: usize,
+    /// Packets that arrived with the same sequence as the one before it
+    pub duplicates: usize,
+    /// Bytes per second received from the driverstation over the last second
+    pub received_bytes_per_sec: f32,
+    /// Bytes per second sent to the driverstation over the last second
+    pub sent_bytes_per_sec: f32,
+}
+
+/// A packet that moved the sequence forward
+#[derive(Debug, Clone)]
+struct Received {
+    at: Instant,
+    sequence: u16,
+    /// how many sequences were skipped over right before this one
+    gap: u16,
+    /// the skipped sequences that showed up late after all
+    recovered: Vec<u16>,
+}
+
+impl Received {
+    fn lost(&self) -> usize {
+        self.gap as usize - self.recovered.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Transfer {
+    at: Instant,
+    bytes: usize,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct LinkTracker {
+    /// only packets that moved the sequence forward, for working out the loss
+    received: VecDeque<Received>,
+    /// every packet including late ones and duplicates
+    received_bytes: VecDeque<Transfer>,
+    sent: VecDeque<Transfer>,
+    last_sequence: Option<u16>,
+    last_arrival: Option<Instant>,
+    last_interval: Option<Duration>,
+    jitter: JitterHistogram,
+    longest_gap: Duration,
+    reordered: usize,
+    duplicates: usize,
+}
+
+impl LinkTracker {
+    pub(crate) fn record_received(&mut self, at: Instant, sequence: u16, bytes: usize) {
+        prune(&mut self.received, at, |r| r.at);
+        prune(&mut self.received_bytes, at, |r| r.at);
+        self.received_bytes.push_back(Transfer { at, bytes });
+
+        if let Some(last) = self.last_arrival {
+            let interval = at.saturating_duration_since(last);
+            self.longest_gap = self.longest_gap.max(interval);
+            if let Some(last_interval) = self.last_interval {
+                self.jitter.record(interval.abs_diff(last_interval));
+            }
+            self.last_interval = Some(interval);
+        }
+        self.last_arrival = Some(at);
+
+        let gap = match self.last_sequence.map(|last| sequence.wrapping_sub(last)) {
+            None => 0,
+            Some(0) => {
+                self.duplicates += 1;
+                return;
+            }
+            Some(ahead @ 1..=0x7FFF) => ahead - 1,
+            Some(_) => {
+                self.reordered += 1;
+                // only un-count it if it falls in a gap we counted and didn't already show up
+                let skipped = self
+                    .received
+                    .iter_mut()
+                    .rev()
+                    .find(|r| (1..=r.gap).contains(&r.sequence.wrapping_sub(sequence)));
+                if let Some(skipped) = skipped {
+                    if !skipped.recovered.contains(&sequence) {
+                        skipped.recovered.push(sequence);
+                    }
+                }
+                return;
+            }
+        };
+
+        self.last_sequence = Some(sequence);
+        self.received.push_back(Received {
+            at,
+            sequence,
+            gap,
+            recovered: Vec::new(),
+        });
+    }
+
+    pub(crate) fn record_sent(&mut self, at: Instant, bytes: usize) {
+        prune(&mut self.sent, at, |s| s.at);
+        self.sent.push_back(Transfer { at, bytes });
+    }
+
+    pub(crate) fn snapshot(&self, now: Instant) -> LinkStats {
+        let within = |at: Instant, window: Duration| now.saturating_duration_since(at) <= window;
+
+        let loss = |window: Duration| {
+            let (expected, lost) = self
+                .received
+                .iter()
+                .filter(|r| within(r.at, window))
+                .fold((0, 0), |(expected, lost), r| {
+                    (expected + 1 + r.gap as usize, lost + r.lost())
+                });
+            if expected == 0 {
+                0.0
+            } else {
+                lost as f32 * 100.0 / expected as f32
+            }
+        };
+
+        let per_sec = |bytes: usize| bytes as f32 / SHORT_WINDOW.as_secs_f32();
+
+        LinkStats {
+            packet_loss_1s: loss(SHORT_WINDOW),
+            packet_loss_10s: loss(WINDOW),
+            jitter: self.jitter,
+            longest_gap: self.longest_gap,
+            reordered: self.reordered,
+            duplicates: self.duplicates,
+            received_bytes_per_sec: per_sec(
+                self.received_bytes
+                    .iter()
+                    .filter(|r| within(r.at, SHORT_WINDOW))
+                    .map(|r| r.bytes)
+                    .sum(),
+            ),
+            sent_bytes_per_sec: per_sec(
+                self.sent
+                    .iter()
+                    .filter(|s| within(s.at, SHORT_WINDOW))
+                    .map(|s| s.bytes)
+                    .sum(),
+            ),
+        }
+    }
+}
+
+fn prune<T>(samples: &mut VecDeque<T>, now: Instant, at: impl Fn(&T) -> Instant) {
+    while samples
+        .front()
+        .is_some_and(|sample| now.saturating_duration_since(at(sample)) > WINDOW)
+    {
+        samples.pop_front();
+    }
+}
+
+impl RoborioCom {
+    /// Rolling statistics about the udp link with the driverstation since it last connected
+    pub fn link_stats(&self) -> LinkStats {
+        self.udp.link_stats.lock().snapshot(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::LinkTracker;
+
+    #[test]
+    pub fn loss_reorder_and_duplicates() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut tracker = LinkTracker::default();
+
+        tracker.record_received(at(0), 10, 100);
+        tracker.record_received(at(20), 11, 100);
+        // 12 arrives late and 13 never does
+        tracker.record_received(at(40), 14, 100);
+        tracker.record_received(at(45), 12, 100);
+        // a second late copy of 12 and one from before we started dont un-count 13
+        tracker.record_received(at(46), 12, 100);
+        tracker.record_received(at(47), 9, 100);
+        tracker.record_sent(at(45), 50);
+
+        let stats = tracker.snapshot(at(50));
+        assert_eq!(stats.reordered, 3);
+        assert_eq!(stats.duplicates, 0);
+        assert_eq!(stats.packet_loss_1s, 20.0);
+        assert_eq!(stats.longest_gap, Duration::from_millis(20));
+        assert_eq!(stats.received_bytes_per_sec, 600.0);
+        assert_eq!(stats.sent_bytes_per_sec, 50.0);
+        assert_eq!(stats.jitter.counts[0], 2);
+        assert_eq!(stats.jitter.total(), 4);
+
+        // a while later the old packets no longer count towards the short window
+        tracker.record_received(at(2000), 15, 100);
+        tracker.record_received(at(2000), 15, 100);
+        let stats = tracker.snapshot(at(2000));
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.packet_loss_1s, 0.0);
+        assert_eq!(stats.packet_loss_10s, 100.0 / 6.0);
+        assert_eq!(stats.longest_gap, Duration::from_millis(1953));
+    }
+}
@@ -0,0 +1,426 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, Weak},
+    time::{Duration, Instant},
+};
+
+use net_comm::robot_to_driverstation::error::{Errors, Warnings};
+
+use crate::RoborioCom;
+
+/// How many messages from the same place (file and line) get sent to the driverstation before the rest are dropped
+///
+/// The defaults let 10 through a second, the next one that gets through says how many were dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub window: Duration,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            burst: 10,
+            window: Duration::from_secs(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Site {
+    window_start: Instant,
+    sent: u32,
+    suppressed: u32,
+}
+
+#[derive(Debug, Default)]
+struct RateLimiter {
+    limit: RateLimit,
+    sites: HashMap<String, Site>,
+}
+
+impl RateLimiter {
+    /// `None` if this message should be dropped, otherwise how many from this site were dropped since the last one sent
+    fn check(&mut self, site: &str, now: Instant) -> Option<u32> {
+        let limit = self.limit;
+        let site = match self.sites.get_mut(site) {
+            Some(site) => site,
+            None => {
+                // a site whose window is over with nothing suppressed is the same as a new one so it can go,
+                // this keeps the map to the sites that logged recently
+                self.sites.retain(|_, site| {
+                    site.suppressed > 0
+                        || now.saturating_duration_since(site.window_start) < limit.window
+                });
+                self.sites.entry(site.to_owned()).or_insert(Site {
+                    window_start: now,
+                    sent: 0,
+                    suppressed: 0,
+                })
+            }
+        };
+        if now.saturating_duration_since(site.window_start) >= limit.window {
+            site.window_start = now;
+            site.sent = 0;
+        }
+        if site.sent >= limit.burst {
+            site.suppressed = site.suppressed.saturating_add(1);
+            return None;
+        }
+        site.sent += 1;
+        Some(std::mem::take(&mut site.suppressed))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Message,
+    Warning,
+    Error,
+}
+
+/// What the logger and layer share, holds the [`RoborioCom`] weakly so the daemon can still stop once
+/// everything else drops it
+#[derive(Debug)]
+struct Forwarder {
+    com: Weak<RoborioCom>,
+    limiter: spin::Mutex<RateLimiter>,
+}
+
+impl Forwarder {
+    fn new(com: &Arc<RoborioCom>) -> Self {
+        Self {
+            com: Arc::downgrade(com),
+            limiter: Default::default(),
+        }
+    }
+
+    /// `site` is used for rate limiting when theres no `loc`, it should name the callsite and not change
+    /// with the message
+    fn forward(&self, severity: Severity, msg: &str, loc: &str, site: &str, stack: &str) {
+        let Some(com) = self.com.upgrade() else {
+            return;
+        };
+        let site = if loc.is_empty() { site } else { loc };
+        let Some(suppressed) = self.limiter.lock().check(site, Instant::now()) else {
+            return;
+        };
+        let msg: Cow<'_, str> = match suppressed {
+            0 => msg.into(),
+            n => format!("{msg} ({n} similar messages suppressed)").into(),
+        };
+        match severity {
+            Severity::Message => com.send_message(&msg),
+            Severity::Warning => com.send_warning(Warnings::Unknown(0), &msg, loc, stack),
+            Severity::Error => com.send_error(Errors::Error, &msg, loc, stack),
+        }
+    }
+}
+
+/// A [`log::Log`] that sends records to the driverstation console
+///
+/// Errors and warnings show up as driverstation errors and warnings with the file and line as their
+/// location, everything else is a plain message
+#[cfg(feature = "log")]
+#[derive(Debug)]
+pub struct DriverstationLogger {
+    forwarder: Forwarder,
+    level: log::LevelFilter,
+}
+
+#[cfg(feature = "log")]
+impl DriverstationLogger {
+    /// Sends [`log::Level::Info`] and up with the default [`RateLimit`]
+    pub fn new(com: &Arc<RoborioCom>) -> Self {
+        Self {
+            forwarder: Forwarder::new(com),
+            level: log::LevelFilter::Info,
+        }
+    }
+
+    pub fn with_level(mut self, level: log::LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.forwarder.limiter.get_mut().limit = limit;
+        self
+    }
+
+    /// Make this the global logger, this fails if one was already set
+    pub fn install(self) -> Result<(), log::SetLoggerError> {
+        log::set_max_level(self.level);
+        log::set_boxed_logger(Box::new(self))
+    }
+}
+
+#[cfg(feature = "log")]
+impl log::Log for DriverstationLogger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let severity = match record.level() {
+            log::Level::Error => Severity::Error,
+            log::Level::Warn => Severity::Warning,
+            log::Level::Info | log::Level::Debug | log::Level::Trace => Severity::Message,
+        };
+        let loc = location(record.file(), record.line());
+        let site = record.module_path().unwrap_or(record.target());
+        self.forwarder
+            .forward(severity, &record.args().to_string(), &loc, site, "");
+    }
+
+    fn flush(&self) {}
+}
+
+fn location(file: Option<&str>, line: Option<u32>) -> String {
+    match (file, line) {
+        (Some(file), Some(line)) => format!("{file}:{line}"),
+        (Some(file), None) => file.to_owned(),
+        (None, _) => String::new(),
+    }
+}
+
+/// A [`tracing_subscriber::Layer`] that sends events to the driverstation console
+///
+/// Errors and warnings show up as driverstation errors and warnings with the file and line as their
+/// location and the spans they happened in (innermost first) as their stack, everything else is a
+/// plain message. Filter it like any other layer
+#[cfg(feature = "tracing")]
+#[derive(Debug)]
+pub struct DriverstationLayer {
+    forwarder: Forwarder,
+}
+
+#[cfg(feature = "tracing")]
+impl DriverstationLayer {
+    /// Sends everything that gets past the layers filter with the default [`RateLimit`]
+    pub fn new(com: &Arc<RoborioCom>) -> Self {
+        Self {
+            forwarder: Forwarder::new(com),
+        }
+    }
+
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.forwarder.limiter.get_mut().limit = limit;
+        self
+    }
+}
+
+/// Formats the fields of an event or span, `message` goes first without its name
+#[cfg(feature = "tracing")]
+#[derive(Debug, Default)]
+struct FieldFormatter {
+    message: String,
+    fields: String,
+}
+
+#[cfg(feature = "tracing")]
+impl tracing_core::field::Visit for FieldFormatter {
+    fn record_debug(&mut self, field: &tracing_core::Field, value: &dyn std::fmt::Debug) {
+        use std::fmt::Write;
+
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            let _ = write!(self.fields, "{}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &tracing_core::Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+}
+
+/// The formatted fields of a span, kept in its extensions so the stack can be built later
+#[cfg(feature = "tracing")]
+#[derive(Debug, Default)]
+struct SpanFields(String);
+
+#[cfg(feature = "tracing")]
+impl<S> tracing_subscriber::Layer<S> for DriverstationLayer
+where
+    S: tracing_core::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    fn on_new_span(
+        &self,
+        attrs: &tracing_core::span::Attributes<'_>,
+        id: &tracing_core::span::Id,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = FieldFormatter::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(SpanFields(fields.fields));
+    }
+
+    fn on_record(
+        &self,
+        id: &tracing_core::span::Id,
+        values: &tracing_core::span::Record<'_>,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = FieldFormatter::default();
+        values.record(&mut fields);
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanFields(old)) = extensions.get_mut::<SpanFields>() {
+            if !old.is_empty() && !fields.fields.is_empty() {
+                old.push(' ');
+            }
+            old.push_str(&fields.fields);
+        }
+    }
+
+    fn on_event(
+        &self,
+        event: &tracing_core::Event<'_>,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let metadata = event.metadata();
+        let severity = match *metadata.level() {
+            tracing_core::Level::ERROR => Severity::Error,
+            tracing_core::Level::WARN => Severity::Warning,
+            _ => Severity::Message,
+        };
+
+        let mut fields = FieldFormatter::default();
+        event.record(&mut fields);
+        let mut msg = fields.message;
+        if !fields.fields.is_empty() {
+            if !msg.is_empty() {
+                msg.push(' ');
+            }
+            msg.push_str(&fields.fields);
+        }
+
+        let mut stack = String::new();
+        for span in ctx.event_scope(event).into_iter().flatten() {
+            if !stack.is_empty() {
+                stack.push('\n');
+            }
+            stack.push_str(span.name());
+            if let Some(SpanFields(fields)) = span.extensions().get::<SpanFields>() {
+                if !fields.is_empty() {
+                    stack.push('{');
+                    stack.push_str(fields);
+                    stack.push('}');
+                }
+            }
+        }
+
+        let loc = location(metadata.file(), metadata.line());
+        let site = format!("{}::{}", metadata.target(), metadata.name());
+        self.forwarder.forward(severity, &msg, &loc, &site, &stack);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{RateLimit, RateLimiter};
+
+    #[test]
+    pub fn rate_limits_each_site() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter {
+            limit: RateLimit {
+                burst: 2,
+                window: Duration::from_secs(1),
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(limiter.check("main.rs:1", start), Some(0));
+        assert_eq!(limiter.check("main.rs:1", start), Some(0));
+        assert_eq!(limiter.check("main.rs:1", start), None);
+        assert_eq!(limiter.check("main.rs:1", start), None);
+        // other sites have their own budget
+        assert_eq!(limiter.check("main.rs:2", start), Some(0));
+
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.check("main.rs:1", later), Some(2));
+        assert_eq!(limiter.check("main.rs:1", later), Some(0));
+        assert_eq!(limiter.check("main.rs:1", later), None);
+    }
+
+    #[test]
+    pub fn forgets_quiet_sites() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter {
+            limit: RateLimit {
+                burst: 1,
+                window: Duration::from_secs(1),
+            },
+            ..Default::default()
+        };
+
+        for i in 0..100 {
+            limiter.check(&format!("main.rs:{i}"), start);
+        }
+        assert_eq!(limiter.check("main.rs:0", start), None);
+        assert_eq!(limiter.sites.len(), 100);
+
+        // only the site with something suppressed is kept after its window
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.check("main.rs:100", later), Some(0));
+        assert_eq!(limiter.sites.len(), 2);
+        assert_eq!(limiter.check("main.rs:0", later), Some(1));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    pub fn tracing_events_become_messages() {
+        use std::sync::Arc;
+
+        use net_comm::robot_to_driverstation::MessageKind;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        use super::DriverstationLayer;
+        use crate::{tcp::test::take_message, RoborioCom};
+
+        let com = Arc::new(RoborioCom::default());
+        let subscriber = tracing_subscriber::registry().with(DriverstationLayer::new(&com));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("auto", step = 2);
+            let _enter = span.enter();
+            tracing::warn!(speed = 1.5, "slipping");
+            tracing::info!("hello");
+        });
+
+        let mut buf = vec![0u8; u16::MAX as usize + 2];
+        match take_message(&com, &mut buf) {
+            MessageKind::Warning {
+                msg, loc, stack, ..
+            } => {
+                assert_eq!(msg, "slipping speed=1.5");
+                assert!(loc.contains("logging.rs:"));
+                assert_eq!(stack, "auto{step=2}");
+            }
+            other => panic!("unexpected message {other:?}"),
+        }
+        assert!(matches!(
+            take_message(&com, &mut buf),
+            MessageKind::Message { msg, .. } if msg == "hello"
+        ));
+    }
+}
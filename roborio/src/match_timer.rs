@@ -0,0 +1,282 @@
+use std::time::{Duration, Instant};
+
+use robot_comm::common::control_code::ControlCode;
+
+use crate::{MatchType, RoborioCom};
+
+/// Where in a match the robot is, see [`RoborioCom::match_timer`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchPhase {
+    /// Nothing has been enabled yet (test mode never counts as part of a match)
+    #[default]
+    PreMatch,
+    Auto,
+    /// Disabled between auto and teleop
+    Transition,
+    Teleop,
+    /// The last [`MatchTimings::endgame`] of teleop
+    Endgame,
+    /// Disabled after teleop, this lasts until the robot is enabled again
+    Post,
+}
+
+/// How long each part of a match is, used when the driverstation doesn't send a countdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchTimings {
+    pub auto: Duration,
+    pub teleop: Duration,
+    pub endgame: Duration,
+}
+
+impl Default for MatchTimings {
+    fn default() -> Self {
+        Self {
+            auto: Duration::from_secs(15),
+            teleop: Duration::from_secs(135),
+            endgame: Duration::from_secs(20),
+        }
+    }
+}
+
+/// A snapshot of the match the robot is in, see [`RoborioCom::match_timer`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MatchTimer {
+    pub phase: MatchPhase,
+    /// Time left in auto or teleop (endgame ends with teleop), `None` for the other phases
+    pub phase_remaining: Option<Duration>,
+    /// Time left in auto and teleop together
+    pub match_remaining: Duration,
+    /// The FMS is attached and says this is a practice, qualification or elimination match
+    pub fms_match: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct MatchTracker {
+    timings: MatchTimings,
+    /// never [`MatchPhase::Endgame`], thats worked out from the time left in teleop
+    phase: MatchPhase,
+    phase_started: Instant,
+    /// the last countdown the driverstation sent this phase and when we got it
+    countdown: Option<(f32, Instant)>,
+    fms_attached: bool,
+}
+
+impl Default for MatchTracker {
+    fn default() -> Self {
+        Self {
+            timings: MatchTimings::default(),
+            phase: MatchPhase::PreMatch,
+            phase_started: Instant::now(),
+            countdown: None,
+            fms_attached: false,
+        }
+    }
+}
+
+impl MatchTracker {
+    /// Forget the current match but keep the timings
+    pub(crate) fn reset(&mut self) {
+        *self = Self {
+            timings: self.timings,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn record(&mut self, control: ControlCode, countdown: Option<f32>, now: Instant) {
+        self.fms_attached = control.is_fms_attached();
+
+        let phase = if control.is_autonomus() {
+            MatchPhase::Auto
+        } else if control.is_teleop() {
+            MatchPhase::Teleop
+        } else if control.is_enabled() {
+            MatchPhase::PreMatch
+        } else {
+            match self.phase {
+                MatchPhase::Auto | MatchPhase::Transition => MatchPhase::Transition,
+                MatchPhase::Teleop | MatchPhase::Endgame | MatchPhase::Post => MatchPhase::Post,
+                MatchPhase::PreMatch => MatchPhase::PreMatch,
+            }
+        };
+        if phase != self.phase {
+            self.phase = phase;
+            self.phase_started = now;
+            self.countdown = None;
+        }
+
+        // the countdown isn't in every packet so keep the last one around
+        if let Some(countdown) = countdown {
+            self.countdown = Some((countdown, now));
+        }
+    }
+
+    fn remaining(&self, length: Duration, now: Instant) -> Duration {
+        match self.countdown {
+            Some((countdown, at)) => Duration::try_from_secs_f32(countdown.max(0.0))
+                .unwrap_or_default()
+                .saturating_sub(now.saturating_duration_since(at)),
+            None => length.saturating_sub(now.saturating_duration_since(self.phase_started)),
+        }
+    }
+
+    pub(crate) fn snapshot(&self, now: Instant, match_type: Option<MatchType>) -> MatchTimer {
+        let timings = self.timings;
+        let (phase, phase_remaining, match_remaining) = match self.phase {
+            MatchPhase::PreMatch => (self.phase, None, timings.auto + timings.teleop),
+            MatchPhase::Auto => {
+                let remaining = self.remaining(timings.auto, now);
+                (self.phase, Some(remaining), remaining + timings.teleop)
+            }
+            MatchPhase::Transition => (self.phase, None, timings.teleop),
+            MatchPhase::Teleop | MatchPhase::Endgame => {
+                let remaining = self.remaining(timings.teleop, now);
+                let phase = if remaining <= timings.endgame {
+                    MatchPhase::Endgame
+                } else {
+                    MatchPhase::Teleop
+                };
+                (phase, Some(remaining), remaining)
+            }
+            MatchPhase::Post => (self.phase, None, Duration::ZERO),
+        };
+
+        MatchTimer {
+            phase,
+            phase_remaining,
+            match_remaining,
+            fms_match: self.fms_attached
+                && matches!(
+                    match_type,
+                    Some(MatchType::Practis | MatchType::Qualifications | MatchType::Eliminations)
+                ),
+        }
+    }
+}
+
+impl RoborioCom {
+    /// The phase of the match and how long is left, interpolated from the last countdown the
+    /// driverstation sent or from [`MatchTimings`] if it hasn't sent one this phase
+    pub fn match_timer(&self) -> MatchTimer {
+        let match_type = self.get_match_type();
+        self.udp
+            .match_tracker
+            .lock()
+            .snapshot(Instant::now(), match_type)
+    }
+
+    pub fn set_match_timings(&self, timings: MatchTimings) {
+        self.udp.match_tracker.lock().timings = timings;
+    }
+
+    pub fn get_match_timings(&self) -> MatchTimings {
+        self.udp.match_tracker.lock().timings
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use robot_comm::common::control_code::ControlCode;
+
+    use super::{MatchPhase, MatchTracker};
+    use crate::{MatchType, RoborioCom};
+
+    #[test]
+    pub fn phases_through_a_match() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let secs = |secs: u64| Some(Duration::from_secs(secs));
+        let mut tracker = MatchTracker::default();
+        let qual = Some(MatchType::Qualifications);
+
+        let mut control = ControlCode::default();
+        control.set_fms_attached(true).set_autonomus();
+        tracker.record(control, None, at(0));
+        let timer = tracker.snapshot(at(0), qual);
+        assert_eq!(timer.phase, MatchPhase::PreMatch);
+        assert!(timer.fms_match);
+        assert!(!tracker.snapshot(at(0), Some(MatchType::None)).fms_match);
+
+        // no countdown so auto runs off the local timings
+        control.set_enabled();
+        tracker.record(control, None, at(1000));
+        let timer = tracker.snapshot(at(6000), qual);
+        assert_eq!(timer.phase, MatchPhase::Auto);
+        assert_eq!(timer.phase_remaining, secs(10));
+        assert_eq!(timer.match_remaining, Duration::from_secs(145));
+
+        control.set_disabled();
+        tracker.record(control, None, at(16000));
+        let timer = tracker.snapshot(at(17000), qual);
+        assert_eq!(timer.phase, MatchPhase::Transition);
+        assert_eq!(timer.match_remaining, Duration::from_secs(135));
+
+        // the countdown wins over the timings and is interpolated between packets
+        control.set_teleop().set_enabled();
+        tracker.record(control, Some(120.0), at(18000));
+        let timer = tracker.snapshot(at(28000), qual);
+        assert_eq!(timer.phase, MatchPhase::Teleop);
+        assert_eq!(timer.phase_remaining, secs(110));
+        tracker.record(control, None, at(120000));
+        assert_eq!(
+            tracker.snapshot(at(120000), qual).phase,
+            MatchPhase::Endgame
+        );
+
+        control.set_disabled();
+        tracker.record(control, Some(0.0), at(140000));
+        let timer = tracker.snapshot(at(140000), qual);
+        assert_eq!(timer.phase, MatchPhase::Post);
+        assert_eq!(timer.match_remaining, Duration::ZERO);
+
+        tracker.reset();
+        assert_eq!(tracker.snapshot(at(0), qual).phase, MatchPhase::PreMatch);
+    }
+
+    /// A driverstation udp packet, with a countdown tag if there is one
+    fn packet(sequence: u16, control: ControlCode, countdown: Option<f32>) -> Vec<u8> {
+        let [hi, lo] = sequence.to_be_bytes();
+        let mut packet = vec![hi, lo, 1, control.to_bits(), 0, 0];
+        if let Some(countdown) = countdown {
+            packet.extend_from_slice(&[5, 7]);
+            packet.extend_from_slice(&countdown.to_be_bytes());
+        }
+        packet
+    }
+
+    #[test]
+    pub fn countdown_only_counts_for_its_own_packet() {
+        let com = RoborioCom::default();
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut send_buf = [0u8; 1024];
+        let mut handle = |packet: &[u8], received_at| {
+            com.handle_udp_datagram(packet, received_at, &mut send_buf, None)
+        };
+        let remaining = |now| {
+            com.udp
+                .match_tracker
+                .lock()
+                .snapshot(now, None)
+                .phase_remaining
+        };
+
+        let mut control = ControlCode::default();
+        control.set_autonomus().set_enabled();
+        assert!(handle(&packet(1, control, Some(10.0)), at(0)));
+        // packets without a countdown keep counting down from the last one
+        assert!(handle(&packet(2, control, None), at(1000)));
+        assert_eq!(remaining(at(2000)), Some(Duration::from_secs(8)));
+
+        assert!(handle(&packet(3, control, Some(0.0)), at(10000)));
+        // auto's last countdown doesn't carry into teleop, even when the packet's tags are bad
+        control.set_teleop();
+        let mut bad_tags = packet(4, control, Some(50.0));
+        bad_tags[7] = 99;
+        assert!(handle(&bad_tags, at(11000)));
+        let timer = com.udp.match_tracker.lock().snapshot(at(11000), None);
+        assert_eq!(timer.phase, MatchPhase::Teleop);
+        assert_eq!(timer.phase_remaining, Some(Duration::from_secs(135)));
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    ops::Deref,
+    time::{Duration, Instant},
+};
+
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+use util::team_number::TeamNumber;
+
+use crate::{PossibleRcSelf, RoborioCom, RoborioComError};
+
+/// How often the mdns thread checks if it should stop
+const POLL: Duration = Duration::from_millis(200);
+/// How long to wait before trying to advertise again, at boot there might not be a multicast interface yet
+const RETRY: Duration = Duration::from_secs(5);
+/// How many times to try saying goodbye before giving up on it
+const GOODBYE_ATTEMPTS: usize = 50;
+
+/// The service the driverstation (and [`util::robot_discovery::find_robot_ip`]) browses for
+pub const SERVICE_TYPE: &str = "_ni-rt._tcp.local.";
+
+/// The instance name a roborio with this team number advertises, `roboRIO-<team>-FRC`
+pub fn instance_name(team_number: TeamNumber) -> String {
+    format!("roboRIO-{team_number}-FRC")
+}
+
+impl RoborioCom {
+    fn mdns_service_info(&self, team_number: TeamNumber) -> mdns_sd::Result<ServiceInfo> {
+        let name = instance_name(team_number);
+        let host_name = format!("{name}.local.");
+        let addrs = self.common.addrs;
+        let new = |ip: &[IpAddr]| {
+            ServiceInfo::new(
+                SERVICE_TYPE,
+                &name,
+                &host_name,
+                ip,
+                addrs.udp_receive_port,
+                HashMap::<String, String>::new(),
+            )
+        };
+        // when bound to everything advertise every interface we have
+        if addrs.bind_ip.is_unspecified() {
+            Ok(new(&[])?.enable_addr_auto())
+        } else {
+            new(&[addrs.bind_ip])
+        }
+    }
+
+    /// Advertise `roboRIO-<team>-FRC` over mDNS for as long as the daemon runs
+    ///
+    /// Does nothing without a team number or if advertising was turned off in the builder
+    pub(super) fn run_mdns_daemon<T: PossibleRcSelf + Deref<Target = Self>>(myself: &T) {
+        let team_number = match myself.common.team_number {
+            Some(team_number) if myself.common.advertise_mdns => team_number,
+            _ => return,
+        };
+
+        let advertise = || {
+            let mdns = ServiceDaemon::new()?;
+            let info = myself.mdns_service_info(team_number)?;
+            let fullname = info.get_fullname().to_owned();
+            mdns.register(info)?;
+            Ok::<_, mdns_sd::Error>((mdns, fullname))
+        };
+        let mut advertised = None;
+        let mut next_attempt = Instant::now();
+        while myself.exists_elsewhere() {
+            if advertised.is_none() && Instant::now() >= next_attempt {
+                match advertise() {
+                    Ok(ok) => advertised = Some(ok),
+                    Err(err) => {
+                        myself.report_error(RoborioComError::MdnsError(err));
+                        next_attempt = Instant::now() + RETRY;
+                    }
+                }
+            }
+            std::thread::sleep(POLL);
+        }
+
+        let Some((mdns, fullname)) = advertised else {
+            return;
+        };
+        // say goodbye so browsers forget us right away instead of waiting for the record to expire
+        let retry = |attempt: &dyn Fn() -> mdns_sd::Result<()>| {
+            for _ in 0..GOODBYE_ATTEMPTS {
+                match attempt() {
+                    Err(mdns_sd::Error::Again) => std::thread::sleep(Duration::from_millis(10)),
+                    _ => return,
+                }
+            }
+        };
+        retry(&|| mdns.unregister(&fullname).map(drop));
+        retry(&|| mdns.shutdown().map(drop));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use util::team_number::TeamNumber;
+
+    use super::instance_name;
+    use crate::RoborioCom;
+
+    #[test]
+    pub fn service_info_for_team() {
+        assert_eq!(instance_name(TeamNumber(254)), "roboRIO-254-FRC");
+
+        let com = RoborioCom::default();
+        let info = com.mdns_service_info(TeamNumber(254)).unwrap();
+        assert_eq!(info.get_fullname(), "roboRIO-254-FRC._ni-rt._tcp.local.");
+        assert_eq!(info.get_hostname(), "roboRIO-254-FRC.local.");
+        assert_eq!(info.get_port(), com.common.addrs.udp_receive_port);
+        // bound to everything so every interface gets advertised
+        assert!(info.is_addr_auto());
+
+        let com = RoborioCom::builder().bind_ip(Ipv4Addr::LOCALHOST).build();
+        let info = com.mdns_service_info(TeamNumber(254)).unwrap();
+        assert!(!info.is_addr_auto());
+        let addrs = info.get_addresses();
+        assert_eq!(addrs.len(), 1);
+        assert!(addrs.contains(&IpAddr::from(Ipv4Addr::LOCALHOST)));
+    }
+}
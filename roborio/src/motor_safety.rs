@@ -0,0 +1,217 @@
+use std::{
+    panic::{RefUnwindSafe, UnwindSafe},
+    sync::{Arc, Weak},
+    time::{Duration, Instant},
+};
+
+use crate::{RoborioCom, RoborioComError, Warnings};
+
+type StopDyn = dyn Fn() + Send + Sync + UnwindSafe + RefUnwindSafe + 'static;
+
+struct MotorSafetyEntry {
+    name: String,
+    stop: Box<StopDyn>,
+    state: spin::Mutex<MotorSafetyState>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MotorSafetyState {
+    expiration: Duration,
+    last_feed: Instant,
+    enabled: bool,
+    /// set when we notice the actuator expired so we only warn once untill its fed again
+    expired: bool,
+}
+
+impl MotorSafetyEntry {
+    fn stop(&self, com: &RoborioCom) {
+        if let Err(err) = std::panic::catch_unwind(&self.stop) {
+            com.report_error(RoborioComError::MotorSafetyStopPanic(
+                self.name.clone(),
+                crate::panic_message(&*err),
+            ));
+        }
+    }
+}
+
+/// Handle to an actuator registered with [`RoborioCom::register_motor_safety`]
+///
+/// The actuator has to be [`MotorSafety::feed`]ed more often than its expiration while the robot is enabled
+/// or it gets stopped. Dropping every clone of the handle unregisters it
+#[derive(Clone)]
+pub struct MotorSafety {
+    entry: Arc<MotorSafetyEntry>,
+}
+
+impl std::fmt::Debug for MotorSafety {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MotorSafety")
+            .field("name", &self.entry.name)
+            .field("state", &*self.entry.state.lock())
+            .finish()
+    }
+}
+
+impl MotorSafety {
+    /// Tell the watchdog the actuator was just updated
+    pub fn feed(&self) {
+        let mut state = self.entry.state.lock();
+        state.last_feed = Instant::now();
+        state.expired = false;
+    }
+
+    pub fn set_expiration(&self, expiration: Duration) {
+        self.entry.state.lock().expiration = expiration;
+    }
+
+    pub fn get_expiration(&self) -> Duration {
+        self.entry.state.lock().expiration
+    }
+
+    /// A disabled actuator never expires, it is still stopped when the robot disables
+    pub fn set_safety_enabled(&self, enabled: bool) {
+        self.entry.state.lock().enabled = enabled;
+    }
+
+    pub fn is_safety_enabled(&self) -> bool {
+        self.entry.state.lock().enabled
+    }
+
+    /// If the actuator has been fed within its expiration (or has safety disabled)
+    pub fn is_alive(&self) -> bool {
+        let state = self.entry.state.lock();
+        !state.enabled || state.last_feed.elapsed() <= state.expiration
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.entry.name
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct MotorSafetyRegistry {
+    entries: Vec<Weak<MotorSafetyEntry>>,
+}
+
+impl std::fmt::Debug for MotorSafetyRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MotorSafetyRegistry")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+impl RoborioCom {
+    /// Register an actuator with the motor safety watchdog
+    ///
+    /// `stop` is called when the actuator isn't fed within `expiration` while enabled, when the robot
+    /// disables and when the driverstation times out
+    pub fn register_motor_safety(
+        &self,
+        name: impl Into<String>,
+        expiration: Duration,
+        stop: impl Fn() + Send + Sync + UnwindSafe + RefUnwindSafe + 'static,
+    ) -> MotorSafety {
+        let entry = Arc::new(MotorSafetyEntry {
+            name: name.into(),
+            stop: Box::new(stop),
+            state: spin::Mutex::new(MotorSafetyState {
+                expiration,
+                last_feed: Instant::now(),
+                enabled: true,
+                expired: false,
+            }),
+        });
+        let mut registry = self.common.motor_safety.lock();
+        registry.entries.retain(|entry| entry.strong_count() > 0);
+        registry.entries.push(Arc::downgrade(&entry));
+        MotorSafety { entry }
+    }
+
+    fn motor_safety_entries(&self) -> Vec<Arc<MotorSafetyEntry>> {
+        let mut registry = self.common.motor_safety.lock();
+        registry.entries.retain(|entry| entry.strong_count() > 0);
+        // stop callbacks might register/drop actuators so dont call them while holding the lock
+        registry.entries.iter().filter_map(Weak::upgrade).collect()
+    }
+
+    /// Call the stop callback of every registered actuator
+    pub fn stop_all_motors(&self) {
+        for entry in self.motor_safety_entries() {
+            entry.stop(self);
+        }
+    }
+
+    /// Stop every actuator that hasn't been fed in time and warn the driverstation about the newly expired ones
+    pub(crate) fn check_motor_safety(&self) {
+        if !self.get_control_code().is_enabled() {
+            return;
+        }
+        for entry in self.motor_safety_entries() {
+            let mut state = entry.state.lock();
+            if !state.enabled || state.last_feed.elapsed() <= state.expiration {
+                continue;
+            }
+            let newly_expired = !std::mem::replace(&mut state.expired, true);
+            drop(state);
+
+            entry.stop(self);
+            if newly_expired {
+                self.send_warning(
+                    Warnings::LoopTimingError,
+                    &format!(
+                        "{}... Output not updated often enough. See https://docs.wpilib.org/motorsafety for more information.",
+                        entry.name
+                    ),
+                    "",
+                    "",
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use crate::RoborioCom;
+
+    #[test]
+    pub fn expired_actuators_are_stopped() {
+        let com = RoborioCom::default();
+        let stops = Arc::new(AtomicUsize::new(0));
+        let stops2 = stops.clone();
+        let safety = com.register_motor_safety("drive", Duration::ZERO, move || {
+            stops2.fetch_add(1, Ordering::Relaxed);
+        });
+
+        // nothing expires while disabled
+        std::thread::sleep(Duration::from_millis(1));
+        com.check_motor_safety();
+        assert_eq!(stops.load(Ordering::Relaxed), 0);
+
+        com.udp.recv.lock().control_code.set_enabled();
+        com.check_motor_safety();
+        assert_eq!(stops.load(Ordering::Relaxed), 1);
+        assert!(!safety.is_alive());
+
+        safety.set_expiration(Duration::from_secs(60));
+        safety.feed();
+        com.check_motor_safety();
+        assert_eq!(stops.load(Ordering::Relaxed), 1);
+
+        com.stop_all_motors();
+        assert_eq!(stops.load(Ordering::Relaxed), 2);
+
+        drop(safety);
+        com.stop_all_motors();
+        assert_eq!(stops.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    backtrace::Backtrace,
+    panic::PanicHookInfo,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{Errors, RoborioCom};
+
+/// How long a panicking thread waits for its report to be written out to the driverstation
+const PANIC_FLUSH_TIMEOUT: Duration = Duration::from_millis(500);
+
+impl RoborioCom {
+    /// Report every panic to the driverstation as an error with the panic message, where it happened
+    /// and a backtrace as the stack
+    ///
+    /// This includes panics that get caught, like the ones in mode switch hooks. The report is flushed
+    /// before the previous hook runs (and before the process aborts or the hook runners restart anything).
+    /// Only a weak reference is kept so this doesn't keep the daemon alive
+    pub fn install_panic_hook(self: &Arc<Self>) {
+        let com = Arc::downgrade(self);
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if let Some(com) = com.upgrade() {
+                com.report_panic(info);
+            }
+            previous(info);
+        }));
+    }
+
+    fn report_panic(&self, info: &PanicHookInfo<'_>) {
+        let thread = std::thread::current();
+        let msg = format!(
+            "thread '{}' panicked: {}",
+            thread.name().unwrap_or("<unnamed>"),
+            crate::panic_message(info.payload())
+        );
+        let location = info
+            .location()
+            .map(|location| location.to_string())
+            .unwrap_or_default();
+        let backtrace = Backtrace::force_capture().to_string();
+
+        // this thread could have panicked while holding the tcp locks so they are only tried
+        self.try_send_error_until(
+            Errors::Error,
+            &msg,
+            &location,
+            &backtrace,
+            Instant::now() + PANIC_FLUSH_TIMEOUT,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        panic::PanicHookInfo,
+        sync::{Arc, PoisonError},
+        time::{Duration, Instant},
+    };
+
+    use net_comm::robot_to_driverstation::{error::Errors, MessageKind};
+
+    use crate::{tcp::test::take_message, test::PANIC_HOOK, RoborioCom};
+
+    type Hook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+    #[test]
+    pub fn panics_become_errors() {
+        let _lock = PANIC_HOOK.lock().unwrap_or_else(PoisonError::into_inner);
+        // hooks can't be cloned so share the one the harness had to put it back after
+        let original: Arc<Hook> = Arc::new(std::panic::take_hook());
+        let chained = original.clone();
+        std::panic::set_hook(Box::new(move |info| chained(info)));
+
+        let com = Arc::new(RoborioCom::default());
+        com.install_panic_hook();
+        let res = std::thread::Builder::new()
+            .name("robot".to_owned())
+            .spawn(|| panic!("boom"))
+            .unwrap()
+            .join();
+        assert!(res.is_err());
+
+        // panicking while holding the send buffer gives up on the report instead of deadlocking
+        let start = Instant::now();
+        let res = std::thread::scope(|s| {
+            s.spawn(|| {
+                let _send_buffer = com.tcp.send_buffer();
+                panic!("while locked")
+            })
+            .join()
+        });
+        assert!(res.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        drop(std::panic::take_hook());
+        std::panic::set_hook(Box::new(move |info| original(info)));
+
+        // tests on other threads panicking while the hook was in could have reported too
+        let mut buf = vec![0u8; u16::MAX as usize + 2];
+        let mut found = false;
+        while !com.tcp.send_buffer().is_empty() {
+            if let MessageKind::Error {
+                err,
+                msg,
+                loc,
+                stack,
+                ..
+            } = take_message(&com, &mut buf)
+            {
+                if msg == "thread 'robot' panicked: boom" {
+                    assert_eq!(err, Errors::Error);
+                    assert!(loc.contains("panic_hook.rs:"));
+                    assert!(!stack.is_empty());
+                    found = true;
+                }
+            }
+        }
+        assert!(found);
+    }
+}
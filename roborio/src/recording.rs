@@ -0,0 +1,421 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::{mpsc, MutexGuard, PoisonError},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use util::buffer_reader::BufferReader;
+
+use crate::{RoborioCom, RoborioComError};
+
+/// Every recording starts with this, the last byte is the format version
+const MAGIC: &[u8; 8] = b"RIOREC\0\x01";
+
+/// How many packets can be waiting for the writer before new ones are dropped, a bit over 20s of udp
+const QUEUE_LEN: usize = 1024;
+
+/// Where a recorded packet came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum RecordedKind {
+    /// A whole udp datagram
+    Udp = 0,
+    /// A tcp frame without its size
+    Tcp = 1,
+}
+
+/// One packet the driverstation sent us
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedPacket {
+    /// When it was received, relative to the start of the recording
+    pub at: Duration,
+    pub kind: RecordedKind,
+    pub data: Vec<u8>,
+}
+
+/// How fast [`RoborioCom::replay`] feeds packets back in
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ReplaySpeed {
+    /// Keep the same gaps between packets as when they were recorded
+    #[default]
+    RealTime,
+    /// Divide the gaps by this, `2.0` replays twice as fast. It has to be above 0 or
+    /// [`RoborioCom::replay`] fails with [`io::ErrorKind::InvalidInput`]
+    Scaled(f64),
+    /// Dont wait between packets at all
+    Unlimited,
+}
+
+/// A running recording, packets are handed off to a thread that writes them so a slow `out` doesn't
+/// hold up the daemon threads
+pub(crate) struct Recorder {
+    packets: mpsc::SyncSender<RecordedPacket>,
+    writer: JoinHandle<io::Result<()>>,
+    started: Instant,
+    /// packets dropped because the writer couldn't keep up
+    dropped: usize,
+    /// if the last packet was dropped, so only the start of a run of drops gets reported
+    dropping: bool,
+}
+
+impl Recorder {
+    /// Wait for everything sent so far to be written and flushed
+    fn finish(self) -> io::Result<()> {
+        drop(self.packets);
+        self.writer
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("the recording thread panicked")))
+    }
+}
+
+/// Runs on the recorders thread untill the sender is dropped or a write fails
+fn write_packets(
+    mut out: Box<dyn Write + Send>,
+    packets: mpsc::Receiver<RecordedPacket>,
+) -> io::Result<()> {
+    for packet in packets {
+        // tcp frames and our udp receive buffer both fit in a u16
+        let len = u16::try_from(packet.data.len())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        out.write_all(&[packet.kind as u8])?;
+        out.write_all(&(packet.at.as_nanos() as u64).to_be_bytes())?;
+        out.write_all(&len.to_be_bytes())?;
+        out.write_all(&packet.data)?;
+    }
+    out.flush()
+}
+
+/// Reads the packets back out of something written by [`RoborioCom::start_recording`]
+#[derive(Debug)]
+pub struct RecordingReader<R> {
+    input: R,
+}
+
+impl<R: Read> RecordingReader<R> {
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let mut magic = [0u8; MAGIC.len()];
+        input.read_exact(&mut magic)?;
+        if magic != *MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a roborio recording",
+            ));
+        }
+        Ok(Self { input })
+    }
+
+    /// The next packet or `None` at the end of the recording
+    pub fn read_packet(&mut self) -> io::Result<Option<RecordedPacket>> {
+        let mut header = [0u8; 1 + 8 + 2];
+        // a recording cut off partway through a packet (like when the robot lost power) just ends there
+        match self.input.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+        let kind = match header[0] {
+            0 => RecordedKind::Udp,
+            1 => RecordedKind::Tcp,
+            kind => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown recorded packet kind {kind}"),
+                ))
+            }
+        };
+        let at = Duration::from_nanos(u64::from_be_bytes(header[1..9].try_into().unwrap()));
+        let mut data = vec![0u8; u16::from_be_bytes([header[9], header[10]]) as usize];
+        match self.input.read_exact(&mut data) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+        Ok(Some(RecordedPacket { at, kind, data }))
+    }
+}
+
+impl<R: Read> Iterator for RecordingReader<R> {
+    type Item = io::Result<RecordedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
+
+impl RoborioCom {
+    // nothing we do while holding this can leave it in a bad state so ignore poisoning
+    fn recorder(&self) -> MutexGuard<'_, Option<Recorder>> {
+        self.common
+            .recorder
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Write every udp datagram and tcp frame the driverstation sends us to `out`, replacing any
+    /// recording that was already running
+    ///
+    /// Replay it with [`RoborioCom::replay`]. Writing is done on a thread of its own so a slow `out`
+    /// doesn't hold up the daemon, it should still be buffered
+    pub fn start_recording(&self, out: impl Write + Send + 'static) -> io::Result<()> {
+        let mut out: Box<dyn Write + Send> = Box::new(out);
+        out.write_all(MAGIC)?;
+        let (packets, receiver) = mpsc::sync_channel(QUEUE_LEN);
+        let writer = std::thread::Builder::new()
+            .name("roborio-recording".to_owned())
+            .spawn(move || write_packets(out, receiver))?;
+        let old = self.recorder().replace(Recorder {
+            packets,
+            writer,
+            started: Instant::now(),
+            dropped: 0,
+            dropping: false,
+        });
+        match old {
+            Some(old) => old.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// [`RoborioCom::start_recording`] to a new file at `path`
+    pub fn start_recording_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.start_recording(BufWriter::new(File::create(path)?))
+    }
+
+    /// Stop recording, this waits for whatever is left to be written and flushed
+    pub fn stop_recording(&self) -> io::Result<()> {
+        let recorder = self.recorder().take();
+        match recorder {
+            Some(recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder().is_some()
+    }
+
+    /// Packets left out of the current recording because `out` couldn't keep up
+    pub fn get_recording_dropped_packets(&self) -> usize {
+        self.recorder()
+            .as_ref()
+            .map_or(0, |recorder| recorder.dropped)
+    }
+
+    pub(crate) fn record_packet(&self, kind: RecordedKind, at: Instant, data: &[u8]) {
+        let mut recorder = self.recorder();
+        let Some(active) = recorder.as_mut() else {
+            return;
+        };
+        let packet = RecordedPacket {
+            at: at.saturating_duration_since(active.started),
+            kind,
+            data: data.to_vec(),
+        };
+        match active.packets.try_send(packet) {
+            Ok(()) => active.dropping = false,
+            Err(mpsc::TrySendError::Full(_)) => {
+                active.dropped += 1;
+                if !std::mem::replace(&mut active.dropping, true) {
+                    drop(recorder);
+                    self.report_error(RoborioComError::RecordingError(io::Error::new(
+                        io::ErrorKind::WouldBlock,
+                        "the recording can't keep up, dropping packets",
+                    )));
+                }
+            }
+            // the writer only hangs up when a write failed, it's done by now so this doesn't wait
+            Err(mpsc::TrySendError::Disconnected(_)) => {
+                let failed = recorder.take();
+                drop(recorder);
+                if let Some(Err(err)) = failed.map(Recorder::finish) {
+                    self.report_error(RoborioComError::RecordingError(err));
+                }
+            }
+        }
+    }
+
+    /// Feed a recording back in as if the driverstation was sending it, blocking untill it ends
+    ///
+    /// Packets go through the same parsing, hooks and handlers as live ones but nothing is sent back.
+    /// The daemon shouldn't be running at the same time or its packets get mixed in. Once the recording
+    /// ends the robot is disabled like the driverstation disconnected
+    pub fn replay(&self, input: impl Read, speed: ReplaySpeed) -> io::Result<()> {
+        if let ReplaySpeed::Scaled(scale) = speed {
+            if scale.is_nan() || scale <= 0.0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("replay speed has to be above 0, not {scale}"),
+                ));
+            }
+        }
+        let reader = RecordingReader::new(input)?;
+        let start = Instant::now();
+        let mut first = None;
+        let mut send_buf = [0u8; 1024];
+
+        let res = (|| {
+            for packet in reader {
+                let packet = packet?;
+                // skip over however long it was recording before the driverstation showed up
+                let at = packet.at.saturating_sub(*first.get_or_insert(packet.at));
+                let due = match speed {
+                    ReplaySpeed::RealTime => Some(at),
+                    ReplaySpeed::Scaled(scale) => {
+                        Duration::try_from_secs_f64(at.as_secs_f64() / scale).ok()
+                    }
+                    ReplaySpeed::Unlimited => None,
+                };
+                if let Some(due) = due {
+                    std::thread::sleep(due.saturating_sub(start.elapsed()));
+                }
+
+                match packet.kind {
+                    RecordedKind::Udp => {
+                        self.handle_udp_datagram(&packet.data, Instant::now(), &mut send_buf, None);
+                    }
+                    RecordedKind::Tcp => {
+                        if let Err(err) = self.read_data(BufferReader::new(&packet.data)) {
+                            self.report_error(RoborioComError::TcpPacketReadError(err))
+                        }
+                    }
+                }
+            }
+            Ok(())
+        })();
+
+        self.set_udp_connected(false);
+        self.force_disable();
+        res
+    }
+
+    /// [`RoborioCom::replay`] a recording file
+    pub fn replay_file(&self, path: impl AsRef<Path>, speed: ReplaySpeed) -> io::Result<()> {
+        self.replay(BufReader::new(File::open(path)?), speed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    use robot_comm::common::control_code::ControlCode;
+
+    use super::{RecordedKind, RecordingReader, ReplaySpeed, QUEUE_LEN};
+    use crate::{event::RoborioEvent, RoborioCom};
+
+    /// Lets the test read back what was written after the recorder took ownership of it
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Blocks every write untill the test lets go of the gate
+    struct Gated(Arc<Mutex<()>>);
+
+    impl Write for Gated {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            drop(self.0.lock().unwrap());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A driverstation udp packet with no tags, from red 1
+    fn packet(sequence: u16, control_code: ControlCode) -> Vec<u8> {
+        let [high, low] = sequence.to_be_bytes();
+        vec![high, low, 1, control_code.to_bits(), 0, 0]
+    }
+
+    #[test]
+    pub fn replays_a_recording() {
+        let recording = Shared::default();
+        let recorder = RoborioCom::default();
+        recorder.start_recording(recording.clone()).unwrap();
+
+        let mut enabled = ControlCode::default();
+        enabled.set_teleop().set_enabled();
+        recorder.record_packet(
+            RecordedKind::Udp,
+            Instant::now(),
+            &packet(1, ControlCode::default()),
+        );
+        recorder.record_packet(RecordedKind::Udp, Instant::now(), &packet(2, enabled));
+        // a game data frame
+        recorder.record_packet(RecordedKind::Tcp, Instant::now(), b"\x0eLRL");
+        recorder.stop_recording().unwrap();
+        assert!(!recorder.is_recording());
+
+        let bytes = recording.0.lock().unwrap().clone();
+        let packets = RecordingReader::new(bytes.as_slice())
+            .unwrap()
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[2].kind, RecordedKind::Tcp);
+
+        let com = RoborioCom::default();
+        let teleop = Arc::new(Mutex::new(false));
+        let seen = teleop.clone();
+        com.set_teleop_hook(move || *seen.lock().unwrap() = true);
+        com.replay(bytes.as_slice(), ReplaySpeed::Unlimited)
+            .unwrap();
+
+        assert!(*teleop.lock().unwrap());
+        assert_eq!(com.get_udp_packets_received(), 2);
+        assert_eq!(com.get_game_data().as_deref(), Some("LRL"));
+        // the recording ended so we're disabled again
+        assert!(com.get_control_code().is_disabled());
+
+        for scale in [0.0, -1.0, f64::NAN] {
+            let err = com
+                .replay(bytes.as_slice(), ReplaySpeed::Scaled(scale))
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    pub fn drops_packets_when_the_writer_stalls() {
+        let gate = Arc::new(Mutex::new(()));
+        let com = RoborioCom::default();
+        com.start_recording(Gated(gate.clone())).unwrap();
+        let events = com.subscribe();
+
+        let stalled = gate.lock().unwrap();
+        for sequence in 0..(QUEUE_LEN + 100) as u16 {
+            com.record_packet(
+                RecordedKind::Udp,
+                Instant::now(),
+                &packet(sequence, ControlCode::default()),
+            );
+        }
+        // the writer might have taken one out before it got stuck
+        assert!(com.get_recording_dropped_packets() >= 99);
+        let errors = events
+            .try_iter()
+            .filter(|event| matches!(event, RoborioEvent::Error(_)))
+            .count();
+        assert_eq!(errors, 1);
+
+        drop(stalled);
+        com.stop_recording().unwrap();
+    }
+}
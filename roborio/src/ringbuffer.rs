@@ -4,6 +4,7 @@ pub struct RingBuffer {
     tail: usize,
     len: usize,
     max_capacity: usize,
+    dropped_tracked: usize,
     data: Vec<u8>,
 }
 
@@ -23,6 +24,7 @@ impl RingBuffer {
             tail: 0,
             len: 0,
             max_capacity: usize::MAX,
+            dropped_tracked: 0,
             data: Vec::new(),
         }
     }
@@ -34,6 +36,7 @@ impl RingBuffer {
             tail: 0,
             len: 0,
             max_capacity: max,
+            dropped_tracked: 0,
             data: Vec::new(),
         }
     }
@@ -49,6 +52,7 @@ impl RingBuffer {
             Err(_err) => return Err(ExceededMaximumCapacity),
         };
 
+        self.make_room_tracked(total_size)?;
         self.resize_or_erase(total_size)?;
         self.write(size.to_be_bytes().as_slice()).unwrap();
         for data in combied {
@@ -119,31 +123,44 @@ impl RingBuffer {
             Err(_err) => return Err(ExceededMaximumCapacity),
         };
 
+        self.make_room_tracked(total_size)?;
         self.resize_or_erase(total_size)?;
         self.write(size.to_be_bytes().as_slice()).unwrap();
         self.write(data).unwrap();
         Ok(())
     }
 
-    pub fn take_tracked(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+    /// The size (including its 2 byte size) of the next tracked frame if there is one
+    pub fn peek_tracked_len(&self) -> Option<usize> {
         if self.len() < 2 {
-            return Ok(0);
+            return None;
         }
         let len = u16::from_be_bytes([
             self.data[self.head],
             self.data[self.wrap_in_buffer(self.head + 1)],
         ]);
-        let start = self.wrap_in_buffer(self.head);
-        let end = self.wrap_in_buffer(self.head + 2 + len as usize);
-        if end <= start {
-            let v = self.current_capacity() - end;
-            buf[..v].copy_from_slice(&self.data[start..]);
-            buf[v..].copy_from_slice(&self.data[..end]);
-        } else {
-            buf[..(len as usize + 2)].copy_from_slice(&self.data[start..end])
+        Some(len as usize + 2)
+    }
+
+    /// How many whole tracked frames have been thrown out to make room for newer ones
+    pub fn dropped_tracked(&self) -> usize {
+        self.dropped_tracked
+    }
+
+    pub fn take_tracked(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+        let Some(total) = self.peek_tracked_len() else {
+            return Ok(0);
+        };
+        if total > self.len() || total > buf.len() {
+            return Err(());
         }
-        self.erase(len as usize + 2);
-        Ok(len as usize + 2)
+        let start = self.wrap_in_buffer(self.head);
+        // the frame might wrap around the end of our data
+        let first = (self.current_capacity() - start).min(total);
+        buf[..first].copy_from_slice(&self.data[start..start + first]);
+        buf[first..total].copy_from_slice(&self.data[..total - first]);
+        self.erase(total);
+        Ok(total)
     }
 
     fn take(&mut self, amount: usize) {
@@ -184,7 +201,8 @@ impl RingBuffer {
 
         if old_capacity != preposed_capacity {
             self.data.resize(preposed_capacity, 0);
-            if self.tail <= self.head {
+            // only move the wrapped part if there actually is one
+            if self.len > 0 && self.tail <= self.head {
                 let (head, tail) = self.data.split_at_mut(self.head);
                 let st = old_capacity - head.len();
                 tail[st..st + self.tail].copy_from_slice(&head[..self.tail]);
@@ -204,14 +222,22 @@ impl RingBuffer {
         }
     }
 
-    fn erase_tracked(&mut self, min_erase: usize) {
-        self.len -= min_erase;
-        self.head += min_erase;
-        self.head = self.wrap_in_buffer(self.head);
-        if self.is_empty() {
-            self.head = 0;
-            self.tail = 0;
+    /// erase the oldest whole frames untill `amount` more bytes fit, so the buffer never starts halfway through a frame
+    fn make_room_tracked(&mut self, amount: usize) -> Result<(), ExceededMaximumCapacity> {
+        if amount > self.max_capacity {
+            return Err(ExceededMaximumCapacity);
+        }
+        while self.max_capacity - self.len < amount {
+            match self.peek_tracked_len() {
+                Some(total) if total <= self.len => {
+                    self.erase(total);
+                    self.dropped_tracked += 1;
+                }
+                // whatever is left isn't a whole frame anyway
+                _ => self.erase(self.len),
+            }
         }
+        Ok(())
     }
 
     pub fn clear(&mut self) {
@@ -267,4 +293,22 @@ mod test {
         bruh.write_tracked(&[63, 64, 255]);
         println!("{:?}", bruh);
     }
+
+    #[test]
+    pub fn overflow_drops_whole_frames() {
+        let mut buf = RingBuffer::with_maximum_capacity(16);
+
+        for i in 0..3u8 {
+            buf.write_tracked(&[i; 4]).unwrap();
+        }
+        // 3 frames of 6 bytes dont fit in 16 so the first one has to go
+        assert_eq!(buf.dropped_tracked(), 1);
+
+        let mut out = [0u8; 16];
+        assert_eq!(buf.take_tracked(&mut out), Ok(6));
+        assert_eq!(out[..6], [0, 4, 1, 1, 1, 1]);
+        assert_eq!(buf.take_tracked(&mut out), Ok(6));
+        assert_eq!(out[..6], [0, 4, 2, 2, 2, 2]);
+        assert!(buf.is_empty());
+    }
 }
@@ -0,0 +1,241 @@
+use std::time::{Duration, Instant};
+
+use robot_comm::common::control_code::ControlCode;
+
+use crate::{RoborioCom, Warnings};
+
+/// The mode the driverstation has the robot in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RobotMode {
+    Disabled,
+    Autonomous,
+    Teleop,
+    Test,
+}
+
+impl From<ControlCode> for RobotMode {
+    fn from(control: ControlCode) -> Self {
+        if control.is_estop() || control.is_disabled() {
+            Self::Disabled
+        } else if control.is_teleop() {
+            Self::Teleop
+        } else if control.is_autonomus() {
+            Self::Autonomous
+        } else if control.is_test() {
+            Self::Test
+        } else {
+            // an invalid mode, the safest thing is to act disabled
+            Self::Disabled
+        }
+    }
+}
+
+/// Robot code driven by a [`TimedRobot`]
+///
+/// The `*_init` functions are called once when the robot enters that mode and the `*_periodic`
+/// ones every loop while its in it. `robot_periodic` is called every loop after the mode specific one
+#[allow(unused_variables)]
+pub trait RobotBase {
+    fn robot_init(&mut self, com: &RoborioCom) {}
+    fn robot_periodic(&mut self, com: &RoborioCom) {}
+
+    fn disabled_init(&mut self, com: &RoborioCom) {}
+    fn disabled_periodic(&mut self, com: &RoborioCom) {}
+
+    fn autonomous_init(&mut self, com: &RoborioCom) {}
+    fn autonomous_periodic(&mut self, com: &RoborioCom) {}
+
+    fn teleop_init(&mut self, com: &RoborioCom) {}
+    fn teleop_periodic(&mut self, com: &RoborioCom) {}
+
+    fn test_init(&mut self, com: &RoborioCom) {}
+    fn test_periodic(&mut self, com: &RoborioCom) {}
+}
+
+/// Runs a [`RobotBase`] at a fixed period from the control code the driverstation sends us
+#[derive(Debug)]
+pub struct TimedRobot<R> {
+    robot: R,
+    period: Duration,
+    /// `None` untill `robot_init` has been called
+    mode: Option<RobotMode>,
+}
+
+impl<R: RobotBase> TimedRobot<R> {
+    pub const DEFAULT_PERIOD: Duration = Duration::from_millis(20);
+
+    pub fn new(robot: R) -> Self {
+        Self::with_period(robot, Self::DEFAULT_PERIOD)
+    }
+
+    pub fn with_period(robot: R, period: Duration) -> Self {
+        Self {
+            robot,
+            period,
+            mode: None,
+        }
+    }
+
+    pub fn get_period(&self) -> Duration {
+        self.period
+    }
+
+    pub fn set_period(&mut self, period: Duration) {
+        self.period = period;
+    }
+
+    /// The mode the last loop ran in
+    pub fn get_mode(&self) -> Option<RobotMode> {
+        self.mode
+    }
+
+    pub fn robot(&self) -> &R {
+        &self.robot
+    }
+
+    pub fn robot_mut(&mut self) -> &mut R {
+        &mut self.robot
+    }
+
+    pub fn into_inner(self) -> R {
+        self.robot
+    }
+
+    /// Run a single loop without any timing
+    pub fn step(&mut self, com: &RoborioCom) {
+        if self.mode.is_none() {
+            com.observe_robot_code(true);
+            self.robot.robot_init(com);
+        }
+
+        let mode = RobotMode::from(com.get_control_code());
+        let entered = self.mode != Some(mode);
+        self.mode = Some(mode);
+
+        match mode {
+            RobotMode::Disabled => {
+                com.observe_robot_disabled();
+                if entered {
+                    self.robot.disabled_init(com);
+                }
+                self.robot.disabled_periodic(com);
+            }
+            RobotMode::Autonomous => {
+                com.observe_robot_autonomus();
+                if entered {
+                    self.robot.autonomous_init(com);
+                }
+                self.robot.autonomous_periodic(com);
+            }
+            RobotMode::Teleop => {
+                com.observe_robot_teleop();
+                if entered {
+                    self.robot.teleop_init(com);
+                }
+                self.robot.teleop_periodic(com);
+            }
+            RobotMode::Test => {
+                com.observe_robot_test();
+                if entered {
+                    self.robot.test_init(com);
+                }
+                self.robot.test_periodic(com);
+            }
+        }
+
+        self.robot.robot_periodic(com);
+    }
+
+    /// Loop forever, see [`TimedRobot::run_while`]
+    pub fn run(&mut self, com: &RoborioCom) -> ! {
+        loop {
+            self.run_while(com, || true);
+        }
+    }
+
+    /// Call [`TimedRobot::step`] every period untill `keep_running` returns false
+    ///
+    /// A loop that takes longer than the period is reported to the driverstation as a warning and the
+    /// next loop starts right away instead of trying to catch up
+    pub fn run_while(&mut self, com: &RoborioCom, mut keep_running: impl FnMut() -> bool) {
+        let mut next = Instant::now();
+        while keep_running() {
+            let start = Instant::now();
+            self.step(com);
+            next += self.period;
+
+            let now = Instant::now();
+            if now > next {
+                com.send_warning(
+                    Warnings::LoopTimingError,
+                    &format!(
+                        "Loop time of {:?} overrun, took {:?}",
+                        self.period,
+                        now - start
+                    ),
+                    "",
+                    "",
+                );
+                next = now;
+            } else {
+                std::thread::sleep(next - now);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::RoborioCom;
+
+    use super::{RobotBase, RobotMode, TimedRobot};
+
+    #[derive(Default)]
+    struct Counts {
+        robot_init: usize,
+        disabled_init: usize,
+        teleop_init: usize,
+        teleop_periodic: usize,
+        robot_periodic: usize,
+    }
+
+    impl RobotBase for Counts {
+        fn robot_init(&mut self, _: &RoborioCom) {
+            self.robot_init += 1;
+        }
+        fn robot_periodic(&mut self, _: &RoborioCom) {
+            self.robot_periodic += 1;
+        }
+        fn disabled_init(&mut self, _: &RoborioCom) {
+            self.disabled_init += 1;
+        }
+        fn teleop_init(&mut self, _: &RoborioCom) {
+            self.teleop_init += 1;
+        }
+        fn teleop_periodic(&mut self, _: &RoborioCom) {
+            self.teleop_periodic += 1;
+        }
+    }
+
+    #[test]
+    pub fn modes_follow_the_control_code() {
+        let com = RoborioCom::default();
+        let mut robot = TimedRobot::new(Counts::default());
+
+        robot.step(&com);
+        assert_eq!(robot.get_mode(), Some(RobotMode::Disabled));
+        assert!(com.get_observed_status().has_robot_code());
+
+        com.udp.recv.lock().control_code.set_teleop().set_enabled();
+        robot.step(&com);
+        robot.step(&com);
+        assert_eq!(robot.get_mode(), Some(RobotMode::Teleop));
+
+        let counts = robot.into_inner();
+        assert_eq!(counts.robot_init, 1);
+        assert_eq!(counts.disabled_init, 1);
+        assert_eq!(counts.teleop_init, 1);
+        assert_eq!(counts.teleop_periodic, 2);
+        assert_eq!(counts.robot_periodic, 3);
+    }
+}
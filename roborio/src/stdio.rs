@@ -0,0 +1,376 @@
+use std::{io, ops::Deref, time::Duration};
+
+#[cfg(unix)]
+use std::{
+    fs::File,
+    io::Write,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+    time::Instant,
+};
+
+#[cfg(unix)]
+use net_comm::robot_to_driverstation::Message;
+
+#[cfg(unix)]
+use crate::tcp::fit_message_strs;
+use crate::{PossibleRcSelf, RoborioCom, RoborioComError};
+
+/// How long the capture thread waits for output before checking if it should stop or if its config changed,
+/// a partial line that has waited this long is sent as is
+const POLL: Duration = Duration::from_millis(200);
+
+/// Lines are split up once they get about this long so something printing without newlines can't grow forever
+#[cfg(unix)]
+const MAX_LINE_LEN: usize = 4096;
+
+/// Which of the process's stdout and stderr get sent to the driverstation console
+///
+/// The defaults capture both and still write everything to wherever they pointed before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StdioCaptureConfig {
+    pub stdout: bool,
+    pub stderr: bool,
+    /// Keep writing the output to the original stdout/stderr (the terminal or a log file) too
+    pub mirror: bool,
+}
+
+impl Default for StdioCaptureConfig {
+    fn default() -> Self {
+        Self {
+            stdout: true,
+            stderr: true,
+            mirror: true,
+        }
+    }
+}
+
+/// The bytes of a line we haven't seen the end of yet and when its first byte was read
+#[cfg(unix)]
+#[derive(Debug, Default)]
+struct LineBuffer {
+    buf: Vec<u8>,
+    started: Option<Instant>,
+}
+
+#[cfg(unix)]
+impl LineBuffer {
+    fn push(&mut self, data: &[u8], now: Instant, lines: &mut Vec<(Instant, String)>) {
+        for chunk in data.split_inclusive(|byte| *byte == b'\n') {
+            self.started.get_or_insert(now);
+            let ended = chunk.ends_with(b"\n");
+            self.buf
+                .extend_from_slice(chunk.strip_suffix(b"\n").unwrap_or(chunk));
+            if ended || self.buf.len() >= MAX_LINE_LEN {
+                self.flush(lines);
+            }
+        }
+    }
+
+    fn flush(&mut self, lines: &mut Vec<(Instant, String)>) {
+        if let Some(started) = self.started.take() {
+            let line = self.buf.strip_suffix(b"\r").unwrap_or(&self.buf);
+            lines.push((started, String::from_utf8_lossy(line).into_owned()));
+            self.buf.clear();
+        }
+    }
+}
+
+/// One fd we took over, everything written to `target` ends up in `read` until it is restored
+#[cfg(unix)]
+#[derive(Debug)]
+struct Redirect {
+    target: RawFd,
+    /// what `target` pointed to before
+    original: File,
+    read: OwnedFd,
+    line: LineBuffer,
+}
+
+#[cfg(unix)]
+fn cvt(res: libc::c_int) -> io::Result<libc::c_int> {
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res)
+    }
+}
+
+#[cfg(unix)]
+impl Redirect {
+    fn new(target: RawFd) -> io::Result<Self> {
+        let mut fds = [0; 2];
+        cvt(unsafe { libc::pipe(fds.as_mut_ptr()) })?;
+        let (read, write) = unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        for fd in fds {
+            cvt(unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) })?;
+        }
+        // we drain the pipe untill theres nothing left so reads can't block
+        cvt(unsafe { libc::fcntl(read.as_raw_fd(), libc::F_SETFL, libc::O_NONBLOCK) })?;
+
+        let original = cvt(unsafe { libc::fcntl(target, libc::F_DUPFD_CLOEXEC, 0) })?;
+        let original = File::from(unsafe { OwnedFd::from_raw_fd(original) });
+        cvt(unsafe { libc::dup2(write.as_raw_fd(), target) })?;
+        // `write` closes here, `target` is the only write end left
+        Ok(Self {
+            target,
+            original,
+            read,
+            line: LineBuffer::default(),
+        })
+    }
+
+    /// Read everything waiting in the pipe, returns false once every write end is closed. Each read is
+    /// stamped with when it happened
+    fn drain(&mut self, mirror: bool, lines: &mut Vec<(Instant, String)>) -> io::Result<bool> {
+        let mut buf = [0u8; 4096];
+        loop {
+            let read =
+                unsafe { libc::read(self.read.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+            let data = match read {
+                0 => return Ok(false),
+                1.. => &buf[..read as usize],
+                _ => {
+                    let err = io::Error::last_os_error();
+                    match err.kind() {
+                        io::ErrorKind::WouldBlock => return Ok(true),
+                        io::ErrorKind::Interrupted => continue,
+                        _ => return Err(err),
+                    }
+                }
+            };
+            if mirror {
+                // theres nowhere to report this that wouldn't just end up back here
+                let _ = (&self.original).write_all(data);
+            }
+            self.line.push(data, Instant::now(), lines);
+        }
+    }
+
+    /// Point `target` back at what it was before, this is also done on drop
+    fn restore(&self) -> io::Result<()> {
+        cvt(unsafe { libc::dup2(self.original.as_raw_fd(), self.target) })?;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Redirect {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+#[cfg(unix)]
+#[derive(Debug)]
+pub(crate) struct StdioCapture {
+    config: StdioCaptureConfig,
+    redirects: Vec<Redirect>,
+}
+
+#[cfg(unix)]
+impl StdioCapture {
+    pub(crate) fn new(config: StdioCaptureConfig) -> io::Result<Self> {
+        let mut targets = Vec::new();
+        if config.stdout {
+            targets.push(libc::STDOUT_FILENO);
+        }
+        if config.stderr {
+            targets.push(libc::STDERR_FILENO);
+        }
+        Self::from_fds(config, &targets)
+    }
+
+    fn from_fds(config: StdioCaptureConfig, targets: &[RawFd]) -> io::Result<Self> {
+        // anything std is still holding on to belongs to before the capture
+        let _ = io::stdout().flush();
+        let redirects = targets
+            .iter()
+            .map(|target| Redirect::new(*target))
+            .collect::<io::Result<_>>()?;
+        Ok(Self { config, redirects })
+    }
+
+    /// Wait up to `timeout` for output and return every line finished since the last call, oldest first
+    pub(crate) fn read_lines(&mut self, timeout: Duration) -> io::Result<Vec<(Instant, String)>> {
+        let mut fds: Vec<_> = self
+            .redirects
+            .iter()
+            .map(|redirect| libc::pollfd {
+                fd: redirect.read.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            })
+            .collect();
+        let res = unsafe {
+            libc::poll(
+                fds.as_mut_ptr(),
+                fds.len() as libc::nfds_t,
+                timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int,
+            )
+        };
+        if let Err(err) = cvt(res) {
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+
+        let mut lines = Vec::new();
+        for redirect in &mut self.redirects {
+            if !redirect.drain(self.config.mirror, &mut lines)? {
+                // someone closed the fd we took over, polling would just spin from here on
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if redirect
+                .line
+                .started
+                .is_some_and(|started| started.elapsed() >= timeout)
+            {
+                redirect.line.flush(&mut lines);
+            }
+        }
+        // stdout and stderr are read one after the other, sorting by when each line was read interleaves
+        // them again. Thats only as good as the stamps, lines written to both before we got to either
+        // pipe still come out stdout first
+        lines.sort_by_key(|(started, _)| *started);
+        Ok(lines)
+    }
+
+    /// Put stdout/stderr back and return whatever was still left in the pipes
+    pub(crate) fn finish(mut self) -> io::Result<Vec<(Instant, String)>> {
+        let _ = io::stdout().flush();
+        let mut lines = Vec::new();
+        for redirect in &mut self.redirects {
+            redirect.restore()?;
+            redirect.drain(self.config.mirror, &mut lines)?;
+            redirect.line.flush(&mut lines);
+        }
+        lines.sort_by_key(|(started, _)| *started);
+        Ok(lines)
+    }
+}
+
+impl RoborioCom {
+    /// Start (or with `None` stop) sending everything the process writes to stdout/stderr to the
+    /// driverstation console, one message per line
+    ///
+    /// This only does anything while the daemon is running. The fds themselves are redirected so output
+    /// from C libraries and child processes is captured too, stopping (or the daemon exiting) puts them back
+    pub fn set_stdio_capture(&self, config: Option<StdioCaptureConfig>) {
+        *self.common.stdio_capture.lock() = config;
+    }
+
+    pub fn get_stdio_capture(&self) -> Option<StdioCaptureConfig> {
+        *self.common.stdio_capture.lock()
+    }
+
+    #[cfg(unix)]
+    pub(super) fn run_stdio_daemon<T: PossibleRcSelf + Deref<Target = Self>>(myself: &T) {
+        let mut current: Option<StdioCaptureConfig> = None;
+        let mut capture: Option<StdioCapture> = None;
+
+        while myself.exists_elsewhere() {
+            let config = *myself.common.stdio_capture.lock();
+            if config != current {
+                current = config;
+                if let Some(capture) = capture.take() {
+                    myself.finish_stdio_capture(capture);
+                }
+                // a failed capture isn't tried again untill the config changes
+                match config.map(StdioCapture::new) {
+                    Some(Ok(new)) => capture = Some(new),
+                    Some(Err(err)) => myself.report_error(RoborioComError::StdioCaptureError(err)),
+                    None => {}
+                }
+            }
+
+            let Some(active) = &mut capture else {
+                std::thread::sleep(POLL);
+                continue;
+            };
+            match active.read_lines(POLL) {
+                Ok(lines) => myself.send_stdio_lines(lines),
+                Err(err) => {
+                    // put the fds back before reporting so the error handler can actually print it
+                    if let Some(capture) = capture.take() {
+                        myself.finish_stdio_capture(capture);
+                    }
+                    myself.report_error(RoborioComError::StdioCaptureError(err));
+                }
+            }
+        }
+
+        if let Some(capture) = capture {
+            myself.finish_stdio_capture(capture);
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub(super) fn run_stdio_daemon<T: PossibleRcSelf + Deref<Target = Self>>(myself: &T) {
+        if myself.common.stdio_capture.lock().is_some() {
+            myself.report_error(RoborioComError::StdioCaptureError(
+                io::ErrorKind::Unsupported.into(),
+            ));
+        }
+    }
+
+    #[cfg(unix)]
+    fn finish_stdio_capture(&self, capture: StdioCapture) {
+        match capture.finish() {
+            Ok(lines) => self.send_stdio_lines(lines),
+            Err(err) => self.report_error(RoborioComError::StdioCaptureError(err)),
+        }
+    }
+
+    #[cfg(unix)]
+    fn send_stdio_lines(&self, lines: Vec<(Instant, String)>) {
+        for (started, line) in lines {
+            let (line, _, _) = fit_message_strs(&line, "", "");
+            self.send_tcp_message_at(Message::info(line), started);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::{
+        io::{Read, Seek, Write},
+        os::fd::AsRawFd,
+        time::Duration,
+    };
+
+    use super::{StdioCapture, StdioCaptureConfig};
+    use crate::test::TempPath;
+
+    #[test]
+    pub fn captures_lines_and_mirrors() {
+        let path = TempPath::new("stdio");
+        let mut file = std::fs::File::options()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+
+        let mut capture =
+            StdioCapture::from_fds(StdioCaptureConfig::default(), &[file.as_raw_fd()]).unwrap();
+        file.write_all(b"hello\nwor").unwrap();
+        file.write_all(b"ld\r\n\npartial").unwrap();
+
+        let lines = capture.read_lines(Duration::from_secs(1)).unwrap();
+        let text: Vec<_> = lines.iter().map(|(_, line)| line.as_str()).collect();
+        assert_eq!(text, ["hello", "world", ""]);
+        assert!(lines.windows(2).all(|lines| lines[0].0 <= lines[1].0));
+
+        let lines = capture.finish().unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].1, "partial");
+
+        // written after the capture stopped so it goes straight to the file
+        file.write_all(b"\nafter\n").unwrap();
+        let mut mirrored = String::new();
+        file.rewind().unwrap();
+        file.read_to_string(&mut mirrored).unwrap();
+        assert_eq!(mirrored, "hello\nworld\r\n\npartial\nafter\n");
+    }
+}
@@ -1,20 +1,43 @@
 use std::{
-    io::{Read, Write},
+    borrow::Cow,
+    io::Read,
     net::{TcpListener, TcpStream},
     ops::Deref,
     panic::{RefUnwindSafe, UnwindSafe},
-    sync::atomic::{AtomicBool, AtomicU16, AtomicU8, AtomicUsize},
+    sync::{
+        atomic::{AtomicBool, AtomicU16, AtomicU64, AtomicU8, AtomicUsize},
+        Mutex, MutexGuard, PoisonError, TryLockError,
+    },
+    time::{Duration, Instant},
 };
 
-use net_comm::robot_to_driverstation::Message;
+use net_comm::robot_to_driverstation::{
+    error::{Errors, Warnings},
+    Message, MessageKind, VersionInfo,
+};
 use num_enum::FromPrimitive;
 // use num_traits::FromPrimitive;
 use util::{
-    buffer_reader::{BufferReader, BufferReaderError, CreateFromBuf},
+    buffer_reader::{BufferReader, BufferReaderError},
+    buffer_writter::{BufferWritter, VecBufferWritter, WriteToBuff},
     super_small_vec::SuperSmallVec,
 };
 
-use crate::{ringbuffer::RingBuffer, PossibleRcSelf, RoborioCom, RoborioComError};
+use crate::{
+    event::RoborioEvent, recording::RecordedKind, ringbuffer::RingBuffer, PossibleRcSelf,
+    RoborioCom, RoborioComError,
+};
+
+mod connection;
+mod tag_handler;
+
+pub(crate) use connection::TcpRoleFilters;
+use connection::{assign_roles, TcpConnection};
+pub use connection::{TcpConnectionRole, TcpConnectionStats, TcpFrameFilter, TcpQueuePolicy};
+use tag_handler::TagHandlers;
+pub use tag_handler::{
+    ControllerDescriptorHandler, GameDataHandler, MatchInfoHandler, TcpTagHandler,
+};
 
 #[derive(Debug)]
 pub(super) struct RoborioTcp {
@@ -29,9 +52,24 @@ pub(super) struct RoborioTcp {
 
     send_buffer: std::sync::Mutex<RingBuffer>,
 
+    connections: std::sync::Mutex<Vec<TcpConnection>>,
+    next_connection_id: AtomicU64,
+    /// bytes each connection can have queued before the queue policy kicks in
+    pub(crate) queue_capacity: usize,
+    pub(crate) queue_policy: TcpQueuePolicy,
+    /// frames dropped from every connections queue, including ones that have since closed
+    queue_overflows: AtomicUsize,
+    pub(crate) role_filters: spin::Mutex<TcpRoleFilters>,
+
     game_data: spin::Mutex<Option<String>>,
     match_info: spin::Mutex<Option<MatchInfo>>,
     controller_info: spin::Mutex<[Option<ControllerInfo>; 6]>,
+    /// bumped every time any of `controller_info` changes
+    controller_info_generation: AtomicUsize,
+
+    version_info: spin::Mutex<Vec<VersionInfo<'static>>>,
+
+    tag_handlers: spin::RwLock<TagHandlers>,
 }
 
 impl UnwindSafe for RoborioTcp {}
@@ -46,13 +84,60 @@ impl Default for RoborioTcp {
             packets_received: Default::default(),
             message_number: Default::default(),
             send_buffer: std::sync::Mutex::new(RingBuffer::with_maximum_capacity(0x20000)),
+            connections: Default::default(),
+            next_connection_id: Default::default(),
+            queue_capacity: 0x20000,
+            queue_policy: Default::default(),
+            queue_overflows: Default::default(),
+            role_filters: Default::default(),
             game_data: Default::default(),
             match_info: Default::default(),
             controller_info: Default::default(),
+            controller_info_generation: AtomicUsize::new(0),
+            version_info: spin::Mutex::new(vec![VersionInfo::LibCVersion(Cow::Borrowed(concat!(
+                "Rust ",
+                env!("CARGO_PKG_VERSION")
+            )))]),
+            tag_handlers: Default::default(),
         }
     }
 }
 
+impl RoborioTcp {
+    // nothing we do while holding these can leave them in a bad state so ignore poisoning
+    pub(crate) fn send_buffer(&self) -> MutexGuard<'_, RingBuffer> {
+        self.send_buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn connections(&self) -> MutexGuard<'_, Vec<TcpConnection>> {
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Waits for the lock like normal with no `give_up_at`, otherwise this only tries untill then. Thats for
+/// when this thread might already be holding it (like when its panicking) so waiting could deadlock
+fn lock_until<T>(mutex: &Mutex<T>, give_up_at: Option<Instant>) -> Option<MutexGuard<'_, T>> {
+    let Some(give_up_at) = give_up_at else {
+        return Some(mutex.lock().unwrap_or_else(PoisonError::into_inner));
+    };
+    loop {
+        match mutex.try_lock() {
+            Ok(guard) => return Some(guard),
+            Err(TryLockError::Poisoned(err)) => return Some(err.into_inner()),
+            Err(TryLockError::WouldBlock) if Instant::now() < give_up_at => {
+                std::thread::yield_now()
+            }
+            Err(TryLockError::WouldBlock) => return None,
+        }
+    }
+}
+
+const VERSION_INFO_REPLY_INTERVAL: Duration = Duration::from_millis(1000);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 #[repr(u8)]
 pub enum MatchType {
@@ -64,7 +149,7 @@ pub enum MatchType {
     Unknown(u8),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MatchInfo {
     pub name: String,
     pub match_type: MatchType,
@@ -72,7 +157,7 @@ pub struct MatchInfo {
     pub replay: u8,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ControllerInfo {
     pub js_type: JoystickType,
     pub is_xbox: bool,
@@ -127,7 +212,8 @@ impl RoborioCom {
         let connection_wait_timeout_ms = 100;
 
         while (*myself).exists_elsewhere() {
-            let listener = match TcpListener::bind("0.0.0.0:1740") {
+            let addrs = myself.common.addrs;
+            let listener = match TcpListener::bind((addrs.bind_ip, addrs.tcp_port)) {
                 Ok(ok) => ok,
                 Err(err) => {
                     myself.report_error(crate::RoborioComError::TcpIoInitError(err));
@@ -137,37 +223,36 @@ impl RoborioCom {
                     continue;
                 }
             };
+            if let Err(err) = listener.set_nonblocking(true) {
+                myself.report_error(crate::RoborioComError::TcpIoInitError(err));
+                std::thread::sleep(std::time::Duration::from_millis(connection_wait_timeout_ms));
+                continue;
+            }
 
-            let connections = std::sync::Mutex::new(Vec::<TcpStream>::new());
             std::thread::scope(|s| {
                 s.spawn(|| {
-                    let mut buf = [0u8; 0x2000];
+                    // big enough for the largest possible frame + its size
+                    let mut buf = vec![0u8; u16::MAX as usize + 2];
 
+                    let mut last_version_reply: Option<Instant> = None;
                     while (*myself).exists_elsewhere() {
                         if myself.tcp.ds_tcp_connected.load(atomic::Ordering::Relaxed) {
-                            // TODO: read in data
-
-                            let res = myself
-                                .tcp
-                                .send_buffer
-                                .lock()
-                                .unwrap()
-                                .take_tracked(&mut buf);
-                            match res {
-                                Ok(size) if size > 0 => {
-                                    let buf = &buf[..size];
-
-                                    for connection in &mut *connections.lock().unwrap() {
-                                        // so uh idrk what the best chunk size to send is?? but a;dssf;lkjatlkj
-                                        connection.write_all(buf).unwrap();
-                                    }
-                                }
-                                Ok(_) => {}
-                                Err(err) => {
-                                    todo!("{:#?}", err)
-                                }
+                            // the driverstation keeps asking untill it gets them so dont reply to every single packet
+                            if myself
+                                .common
+                                .request_info
+                                .swap(false, atomic::Ordering::Relaxed)
+                                && last_version_reply
+                                    .is_none_or(|last| last.elapsed() > VERSION_INFO_REPLY_INTERVAL)
+                            {
+                                last_version_reply = Some(Instant::now());
+                                myself.send_version_info(&myself.get_version_info());
                             }
+                            myself.send_usage_report_if_changed();
+
+                            myself.fill_connection_queues(&mut buf, None);
                         }
+                        myself.flush_connections(None);
 
                         std::thread::sleep(std::time::Duration::from_millis(20));
                     }
@@ -176,66 +261,48 @@ impl RoborioCom {
                 // tcp packet recieving from ONLY the currently connected driverstation device.
                 s.spawn(|| {
                     while (*myself).exists_elsewhere() {
-                        let mut lock = connections.lock().unwrap();
-                        let mut ds_stream = None;
-
-                        lock.retain(|stream: &TcpStream| {
-                            match stream.peer_addr().map(|ok| Some(ok.ip())) {
-                                Ok(ok) => {
-                                    if ok == *myself.common.driverstation_ip.lock() {
-                                        match stream.try_clone() {
-                                            Ok(ok) => {
-                                                ds_stream = Some(ok);
-                                                true
-                                            }
-                                            Err(err) => {
-                                                myself.report_error(
-                                                    crate::RoborioComError::TcpIoInitError(err),
-                                                );
-                                                false
-                                            }
-                                        }
-                                    } else {
-                                        true
-                                    }
-                                }
-                                Err(err) => {
-                                    myself.report_error(crate::RoborioComError::TcpIoGeneralError(
-                                        err,
-                                    ));
-                                    false
-                                }
-                            }
-                        });
+                        let driverstation_ip = *myself.common.driverstation_ip.lock();
+                        let mut lock = myself.tcp.connections();
+                        assign_roles(&mut lock, driverstation_ip);
+                        let ds_stream = lock
+                            .iter()
+                            .find(|connection| connection.role == TcpConnectionRole::Driverstation)
+                            .map(|connection| (connection.id, connection.stream.try_clone()));
                         drop(lock);
 
-                        if let Some(mut stream) = ds_stream {
-                            match myself.handle_stream_read(&mut stream, myself) {
-                                Ok(_) => {
-                                    myself
-                                        .tcp
-                                        .ds_tcp_connected
-                                        .store(false, atomic::Ordering::Release);
-                                }
-                                Err(err) => {
-                                    myself
-                                        .tcp
-                                        .ds_tcp_connected
-                                        .store(false, atomic::Ordering::Release);
-                                    connections.lock().unwrap().retain(|s2| {
-                                        match (stream.peer_addr(), s2.peer_addr()) {
-                                            (Ok(addr), Ok(addr2)) => addr != addr2,
-                                            _ => false,
-                                        }
-                                    });
-                                    myself.report_error(err);
-                                }
+                        let ds_stream = match ds_stream {
+                            Some((ds_id, Ok(stream))) => Some((ds_id, stream)),
+                            Some((ds_id, Err(err))) => {
+                                myself
+                                    .tcp
+                                    .connections()
+                                    .retain(|connection| connection.id != ds_id);
+                                myself.report_error(crate::RoborioComError::TcpIoInitError(err));
+                                None
+                            }
+                            None => None,
+                        };
+
+                        if let Some((ds_id, mut stream)) = ds_stream {
+                            let res = myself.handle_stream_read(ds_id, &mut stream, myself);
+                            myself
+                                .tcp
+                                .ds_tcp_connected
+                                .store(false, atomic::Ordering::Release);
+                            // the driverstation sends them all again when it reconnects
+                            myself.clear_controller_info();
+                            if let Err(err) = res {
+                                myself
+                                    .tcp
+                                    .connections()
+                                    .retain(|connection| connection.id != ds_id);
+                                myself.report_error(err);
                             }
                             let reset_strength =
                                 myself.tcp.reset_con.swap(0, atomic::Ordering::Relaxed);
                             if reset_strength > 0 {
                                 // drop all connections
-                                connections.lock().unwrap().clear()
+                                myself.tcp.connections().clear()
                             }
                             if reset_strength > 1 {}
                         } else {
@@ -246,11 +313,28 @@ impl RoborioCom {
                     }
                 });
 
-                // TODO: make this non blocking or timeout
-                for stream in listener.incoming() {
-                    match stream {
-                        Ok(stream) => {
-                            connections.lock().unwrap().push(stream);
+                // poll so we notice when the daemon is shut down
+                while (*myself).exists_elsewhere() {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let id = myself
+                                .tcp
+                                .next_connection_id
+                                .fetch_add(1, atomic::Ordering::Relaxed);
+                            match TcpConnection::new(id, stream) {
+                                Ok(connection) => {
+                                    let driverstation_ip = *myself.common.driverstation_ip.lock();
+                                    let mut connections = myself.tcp.connections();
+                                    connections.push(connection);
+                                    assign_roles(&mut connections, driverstation_ip);
+                                }
+                                Err(err) => {
+                                    myself.report_error(crate::RoborioComError::TcpIoInitError(err))
+                                }
+                            }
+                        }
+                        Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                            std::thread::sleep(std::time::Duration::from_millis(20));
                         }
                         Err(err) => {
                             myself.report_error(crate::RoborioComError::TcpIoInitError(err))
@@ -259,10 +343,117 @@ impl RoborioCom {
                 }
             });
         }
+        myself.tcp.connections().clear();
+        myself
+            .tcp
+            .ds_tcp_connected
+            .store(false, atomic::Ordering::Release);
+    }
+
+    /// Move every frame we can from the shared send buffer into each connections own queue, does nothing
+    /// if the locks cant be had by `give_up_at` (see [`lock_until`])
+    fn fill_connection_queues(&self, buf: &mut [u8], give_up_at: Option<Instant>) {
+        let capacity = self.tcp.queue_capacity;
+        let Some(mut connections) = lock_until(&self.tcp.connections, give_up_at) else {
+            return;
+        };
+        let Some(mut send_buffer) = lock_until(&self.tcp.send_buffer, give_up_at) else {
+            return;
+        };
+        let filters = *self.tcp.role_filters.lock();
+        while let Some(len) = send_buffer.peek_tracked_len() {
+            // only the driverstation holds things up, a stalled observer shouldn't stop it getting anything.
+            // we can only check the filters once its taken out so wait for it even if it wont get this one
+            if self.tcp.queue_policy == TcpQueuePolicy::Block
+                && !connections
+                    .iter()
+                    .filter(|connection| connection.role == TcpConnectionRole::Driverstation)
+                    .all(|connection| connection.has_room(len, capacity))
+            {
+                break;
+            }
+            match send_buffer.take_tracked(buf) {
+                Ok(0) => break,
+                Ok(size) => {
+                    for connection in connections.iter_mut() {
+                        if !filters.get(connection.role).allows(&buf[..size]) {
+                            continue;
+                        }
+                        let dropped = connection.push(&buf[..size], capacity);
+                        self.tcp
+                            .queue_overflows
+                            .fetch_add(dropped, atomic::Ordering::Relaxed);
+                    }
+                }
+                Err(()) => {
+                    send_buffer.clear();
+                    // the error handler might want to send something so dont hold anything
+                    drop(send_buffer);
+                    drop(connections);
+                    self.report_error(RoborioComError::TcpSendBufferCorrupted);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Write out what we can of every connections queue, closing the ones that fail
+    fn flush_connections(&self, give_up_at: Option<Instant>) {
+        let Some(mut connections) = lock_until(&self.tcp.connections, give_up_at) else {
+            return;
+        };
+        let mut errors = Vec::new();
+        connections.retain_mut(|connection| match connection.flush() {
+            Ok(()) => true,
+            Err(err) => {
+                errors.push(err);
+                false
+            }
+        });
+        drop(connections);
+        for err in errors {
+            self.report_error(RoborioComError::TcpIoSendError(err));
+        }
+    }
+
+    /// Write everything that has been queued out to the connections now instead of waiting for the
+    /// daemon, returns false if that didn't finish within `timeout`
+    ///
+    /// Frames are only handed to the connections while the driverstation is connected, without it
+    /// this only writes out what the connections already had queued
+    pub fn flush_tcp(&self, timeout: Duration) -> bool {
+        self.flush_tcp_until(Instant::now() + timeout, false)
+    }
+
+    /// With `try_locks` the locks are only tried (see [`lock_until`]) so this can't deadlock if this
+    /// thread already holds one, anything it cant lock just doesn't get flushed
+    fn flush_tcp_until(&self, deadline: Instant, try_locks: bool) -> bool {
+        let give_up_at = try_locks.then_some(deadline);
+        let mut buf = vec![0u8; u16::MAX as usize + 2];
+        loop {
+            let connected = self.tcp.ds_tcp_connected.load(atomic::Ordering::Acquire);
+            if connected {
+                self.fill_connection_queues(&mut buf, give_up_at);
+            }
+            self.flush_connections(give_up_at);
+
+            if (!connected
+                || lock_until(&self.tcp.send_buffer, give_up_at).is_some_and(|buf| buf.is_empty()))
+                && lock_until(&self.tcp.connections, give_up_at)
+                    .is_some_and(|connections| connections.iter().all(TcpConnection::is_empty))
+            {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
     }
 
     fn handle_stream_read<T: 'static + Send + Sync + PossibleRcSelf + Deref<Target = Self>>(
         &self,
+        id: u64,
         stream: &mut TcpStream,
         myself: &T,
     ) -> Result<(), RoborioComError> {
@@ -286,6 +477,12 @@ impl RoborioCom {
                     return Err(crate::RoborioComError::TcpIoGeneralError(err))
                 },
             }
+            // a newer connection from the driverstation took over (or this one was kicked)
+            if !self.tcp.connections().iter().any(|connection| {
+                connection.id == id && connection.role == TcpConnectionRole::Driverstation
+            }) {
+                return Ok(());
+            }
             };
         }
 
@@ -333,6 +530,8 @@ impl RoborioCom {
                 .ds_tcp_connected
                 .store(true, atomic::Ordering::Release);
 
+            self.record_packet(RecordedKind::Tcp, Instant::now(), buf);
+
             let buf = BufferReader::new(buf);
 
             if let Err(err) = self.read_data(buf) {
@@ -346,81 +545,33 @@ impl RoborioCom {
         Ok(())
     }
 
-    fn read_data(&self, mut buf: BufferReader<'_>) -> Result<(), BufferReaderError> {
-        match buf.read_u8()? {
-            0x02 => {
-                let index = buf.read_u8()?;
-                let is_xbox = buf.read_u8()? == 1;
-
-                let c = ControllerInfo {
-                    is_xbox,
-                    js_type: JoystickType::from_primitive(buf.read_u8()?),
-                    name: buf.read_short_str()?.to_owned(),
-                    axis: {
-                        let mut axis = SuperSmallVec::new();
-                        for _ in 0..buf.read_u8()? {
-                            axis.push(AxisType::from_primitive(buf.read_u8()?))
-                        }
-                        axis
-                    },
-                    buttons: buf.read_u8()?,
-                    povs: buf.read_u8()?,
-                };
-
-                if let Some(t) = self.tcp.controller_info.lock().get_mut(index as usize) {
-                    *t = Some(c);
-                } else {
-                    todo!("out of bounds error")
-                }
-            }
-            0x07 => {
-                let match_info = MatchInfo {
-                    name: buf.read_short_str()?.to_owned(),
-                    match_type: MatchType::from_primitive(buf.read_u8()?),
-                    match_number: buf.read_u16()?,
-                    replay: buf.read_u8()?,
-                };
-                *self.tcp.match_info.lock() = Some(match_info);
-            }
-            0x0E => {
-                //Game Data
-                *self.tcp.game_data.lock() =
-                    Some(buf.read_str(buf.remaining_buf_len())?.to_owned());
-            }
-            val => {
-                println!("Unknown data tag: {val:02X}")
+    pub(crate) fn read_data(&self, mut buf: BufferReader<'_>) -> Result<(), BufferReaderError> {
+        let tag = buf.read_u8()?;
+        // dont hold the lock while handling so handlers can change the handlers
+        let handler = self.tcp.tag_handlers.read().get(tag);
+        match handler {
+            Some(handler) => handler.handle(self, tag, buf),
+            None => {
+                self.report_error(RoborioComError::TcpUnknownTag(tag));
+                Ok(())
             }
         }
-        Ok(())
     }
 }
 
 impl RoborioCom {
-    pub fn send_zero_code(&self, msg: &str) {
-        //0x00
-        if let Err(err) = self
-            .tcp
-            .send_buffer
-            .lock()
-            .unwrap()
-            .write_combined_tracked(&[&[0x01], msg.as_bytes()])
-        {
-            print!("{:#?}", err);
+    /// Queue a frame made of `data` to be sent to every connection
+    pub(crate) fn queue_tcp_frame(&self, data: &[&[u8]]) {
+        let res = self.tcp.send_buffer().write_combined_tracked(data);
+        if res.is_err() {
+            let len = data.iter().map(|data| data.len()).sum();
+            self.report_error(RoborioComError::TcpFrameTooLarge(len));
         }
     }
 
-    pub fn send_usage_report(&self) {
-        //0x01
-
-        // if let Err(err) = self
-        //     .tcp
-        //     .send_buffer
-        //     .lock()
-        //     .unwrap()
-        //     .write_combined_tracked(&[&[0x01], msg.as_bytes()])
-        // {
-        //     print!("{:#?}", err);
-        // }
+    pub fn send_zero_code(&self, msg: &str) {
+        //0x00
+        self.queue_tcp_frame(&[&[0x01], msg.as_bytes()]);
     }
 
     pub fn send_disable_faults(&self, coms: u16, v12: u16) {
@@ -428,15 +579,7 @@ impl RoborioCom {
         let coms = coms.to_be_bytes();
         let v12 = v12.to_be_bytes();
         let data = [0x04, coms[0], coms[1], v12[0], v12[1]];
-        if let Err(err) = self
-            .tcp
-            .send_buffer
-            .lock()
-            .unwrap()
-            .write_tracked(data.as_slice())
-        {
-            println!("{:#?}", err);
-        }
+        self.queue_tcp_frame(&[&data]);
     }
 
     pub fn send_rail_faults(&self, short_6v: u16, short_5v: u16, short_3_3v: u16) {
@@ -453,61 +596,101 @@ impl RoborioCom {
             short_3_3v[0],
             short_3_3v[1],
         ];
-        if let Err(err) = self
-            .tcp
-            .send_buffer
-            .lock()
-            .unwrap()
-            .write_tracked(data.as_slice())
-        {
-            println!("{:#?}", err);
-        }
+        self.queue_tcp_frame(&[&data]);
     }
 
-    pub fn send_version_info(&self, version_info: &[()]) {
+    pub fn send_version_info(&self, version_info: &[VersionInfo<'_>]) {
         // 0x0a
+        for kind in version_info {
+            self.send_tcp_message(Message {
+                kind: MessageKind::VersionInfo { kind: kind.clone() },
+            });
+        }
     }
 
-    pub fn send_warning(&self) {
-        let msg_num = self
-            .tcp
-            .message_number
-            .fetch_add(1, atomic::Ordering::Relaxed);
-
-        let ms = msg_num as u32;
+    /// Send a warning that shows up in the driverstation console/log
+    ///
+    /// `location` is usually a file and line and `stack` the call stack, either can be empty
+    pub fn send_warning(&self, warn: Warnings, msg: &str, location: &str, stack: &str) {
+        let (msg, location, stack) = fit_message_strs(msg, location, stack);
+        self.send_tcp_message(Message::warn(msg, warn, location, stack));
     }
 
-    pub fn send_error(&self) {
-        let msg_num = self
-            .tcp
-            .message_number
-            .fetch_add(1, atomic::Ordering::Relaxed);
+    /// Send an error that shows up in the driverstation console/log
+    ///
+    /// `location` is usually a file and line and `stack` the call stack, either can be empty
+    pub fn send_error(&self, err: Errors, msg: &str, location: &str, stack: &str) {
+        let (msg, location, stack) = fit_message_strs(msg, location, stack);
+        self.send_tcp_message(Message::error(msg, err, location, stack));
+    }
 
-        let ms = msg_num as u32;
+    /// [`RoborioCom::send_error`] and [`RoborioCom::flush_tcp`] for when this thread might already hold
+    /// the tcp locks (like when its panicking), this only tries to lock them untill `deadline` and
+    /// skips what it cant. Returns false if it didn't all get written out
+    pub(crate) fn try_send_error_until(
+        &self,
+        err: Errors,
+        msg: &str,
+        location: &str,
+        stack: &str,
+        deadline: Instant,
+    ) -> bool {
+        let (msg, location, stack) = fit_message_strs(msg, location, stack);
+        let Some(writter) =
+            self.write_tcp_message(Message::error(msg, err, location, stack), Instant::now())
+        else {
+            return false;
+        };
+        let Some(mut send_buffer) = lock_until(&self.tcp.send_buffer, Some(deadline)) else {
+            return false;
+        };
+        // fit_message_strs means this always fits
+        let _ = send_buffer.write_combined_tracked(&[writter.curr_buf()]);
+        drop(send_buffer);
+        self.flush_tcp_until(deadline, true)
     }
 
     pub fn send_message(&self, msg: &str) {
-        let msg_num = self
-            .tcp
-            .message_number
-            .fetch_add(1, atomic::Ordering::Relaxed);
+        let (msg, _, _) = fit_message_strs(msg, "", "");
+        self.send_tcp_message(Message::info(msg));
+    }
 
-        let ms = msg_num as u32;
+    /// Stamps the message with the current time/message number and queues it to be sent
+    pub(crate) fn send_tcp_message(&self, message: Message<'_>) {
+        self.send_tcp_message_at(message, Instant::now())
+    }
 
-        if let Err(err) = self
-            .tcp
-            .send_buffer
-            .lock()
-            .unwrap()
-            .write_combined_tracked(&[
-                &[0x0C],
-                &ms.to_be_bytes(),
-                &msg_num.to_be_bytes(),
-                msg.as_bytes(),
-            ])
-        {
-            print!("{:#?}", err);
+    /// Like [`RoborioCom::send_tcp_message`] but stamped with when it actually happened
+    pub(crate) fn send_tcp_message_at(&self, message: Message<'_>, at: Instant) {
+        if let Some(writter) = self.write_tcp_message(message, at) {
+            self.queue_tcp_frame(&[writter.curr_buf()]);
+        }
+    }
+
+    /// Stamps the message with `at` and the next message number and writes out the frame for it
+    fn write_tcp_message(&self, mut message: Message<'_>, at: Instant) -> Option<VecBufferWritter> {
+        if matches!(
+            message.kind,
+            MessageKind::Message { .. } | MessageKind::Warning { .. } | MessageKind::Error { .. }
+        ) {
+            let msg_num = self
+                .tcp
+                .message_number
+                .fetch_add(1, atomic::Ordering::Relaxed);
+            message.set_msg_num(msg_num);
+            message.set_ms(
+                at.saturating_duration_since(self.common.created)
+                    .as_millis() as u32,
+            );
+        }
+
+        let mut writter = VecBufferWritter::new();
+        if let Err(err) = message.write_to_buf(&mut writter) {
+            // fit_message_strs should make this impossible
+            self.report_error(RoborioComError::TcpMessageWriteError(err));
+            return None;
         }
+        Some(writter)
     }
 
     pub fn send_underline_5v_disabled(&self, disable_5v: u16, underline: [u8; 3]) {
@@ -521,19 +704,54 @@ impl RoborioCom {
             underline[1],
             underline[2],
         ];
-        if let Err(err) = self
-            .tcp
-            .send_buffer
-            .lock()
-            .unwrap()
-            .write_tracked(data.as_slice())
-        {
-            println!("{:#?}", err);
-        }
+        self.queue_tcp_frame(&[&data]);
     }
 }
 
+/// Each frame has a u16 size so everything we send has to fit in that, this cuts the strings down
+/// (message first then location then stack) so the whole encoded message will fit
+pub(crate) fn fit_message_strs<'a>(
+    msg: &'a str,
+    location: &'a str,
+    stack: &'a str,
+) -> (&'a str, &'a str, &'a str) {
+    // tag, ms, msg num, the 1, code, error flag and 3 string lengths
+    const HEADER: usize = 1 + 4 + 2 + 2 + 4 + 1 + 2 * 3;
+    let mut remaining = u16::MAX as usize - HEADER;
+
+    let mut fit = |str: &'a str| {
+        let mut end = str.len().min(remaining);
+        while !str.is_char_boundary(end) {
+            end -= 1;
+        }
+        remaining -= end;
+        &str[..end]
+    };
+    (fit(msg), fit(location), fit(stack))
+}
+
 impl RoborioCom {
+    /// Add an entry to what we reply with when the driverstation asks for version info (shown in the diagnostics tab)
+    pub fn add_version_info(&self, version_info: VersionInfo<'static>) {
+        self.tcp.version_info.lock().push(version_info);
+    }
+
+    /// Replace every version info entry, returning the old ones
+    pub fn set_version_info(
+        &self,
+        version_info: Vec<VersionInfo<'static>>,
+    ) -> Vec<VersionInfo<'static>> {
+        std::mem::replace(&mut *self.tcp.version_info.lock(), version_info)
+    }
+
+    pub fn get_version_info(&self) -> Vec<VersionInfo<'static>> {
+        self.tcp.version_info.lock().clone()
+    }
+
+    pub fn clear_version_info(&self) {
+        self.tcp.version_info.lock().clear()
+    }
+
     pub fn get_game_data(&self) -> Option<String> {
         self.tcp.game_data.lock().clone()
     }
@@ -558,6 +776,78 @@ impl RoborioCom {
         self.tcp.match_info.lock().clone()
     }
 
+    /// Every open tcp connection, its role and the state of its outbound queue
+    pub fn get_tcp_connections(&self) -> Vec<TcpConnectionStats> {
+        self.tcp
+            .connections()
+            .iter()
+            .map(|connection| connection.stats())
+            .collect()
+    }
+
+    /// Close a tcp connection (see [`RoborioCom::get_tcp_connections`] for the ids), returns false if
+    /// there wasn't one with that id
+    ///
+    /// Kicking the driverstation only lasts untill it reconnects
+    pub fn kick_tcp_connection(&self, id: u64) -> bool {
+        let mut connections = self.tcp.connections();
+        let len = connections.len();
+        connections.retain(|connection| connection.id != id);
+        connections.len() != len
+    }
+
+    /// Choose which frames connections with `role` get sent, by default the driverstation gets
+    /// everything and observers only get the console
+    pub fn set_tcp_role_filter(&self, role: TcpConnectionRole, filter: TcpFrameFilter) {
+        *self.tcp.role_filters.lock().get_mut(role) = filter;
+    }
+
+    pub fn get_tcp_role_filter(&self, role: TcpConnectionRole) -> TcpFrameFilter {
+        self.tcp.role_filters.lock().get(role)
+    }
+
+    /// Frames dropped from connection queues because they were full, this includes connections that are now closed
+    pub fn get_tcp_queue_overflows(&self) -> usize {
+        self.tcp.queue_overflows.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Frames dropped from the shared send buffer because nothing was taking them out fast enough
+    pub fn get_tcp_send_buffer_overflows(&self) -> usize {
+        self.tcp.send_buffer().dropped_tracked()
+    }
+
+    /// Forget every controller descriptor, emitting a [`RoborioEvent::ControllerInfo`] with no info for
+    /// each one there was
+    pub(crate) fn clear_controller_info(&self) {
+        let old = std::mem::take(&mut *self.tcp.controller_info.lock());
+        self.tcp
+            .controller_info_generation
+            .fetch_add(1, atomic::Ordering::Release);
+        for (index, info) in old.into_iter().enumerate() {
+            if info.is_some() {
+                self.emit_event(RoborioEvent::ControllerInfo { index, info: None });
+            }
+        }
+    }
+
+    /// Changes every time a controller descriptor does, so anything checking them only has to look again
+    /// when this changed
+    pub(crate) fn controller_info_generation(&self) -> usize {
+        self.tcp
+            .controller_info_generation
+            .load(atomic::Ordering::Acquire)
+    }
+
+    /// Look at the descriptor for `controller` without cloning it, the tcp thread is held up untill `f` returns
+    pub(crate) fn with_controller_info<R>(
+        &self,
+        controller: u8,
+        f: impl FnOnce(Option<&ControllerInfo>) -> R,
+    ) -> R {
+        let lock = self.tcp.controller_info.lock();
+        f(lock.get(controller as usize).and_then(Option::as_ref))
+    }
+
     pub fn get_controller_info(&self, controller: u8) -> Option<ControllerInfo> {
         self.tcp
             .controller_info
@@ -629,3 +919,192 @@ impl RoborioCom {
             .map(|c| c.name.clone())
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{
+        borrow::Cow,
+        net::{Ipv4Addr, TcpListener, TcpStream},
+    };
+
+    use net_comm::robot_to_driverstation::{
+        error::{Errors, Warnings},
+        Message, MessageKind, VersionInfo,
+    };
+    use util::buffer_reader::{BufferReader, CreateFromBuf};
+
+    use super::{assign_roles, TcpConnection};
+    use crate::{RoborioCom, TcpConnectionRole, TcpFrameFilter, TcpQueuePolicy};
+
+    pub(crate) fn take_message(com: &RoborioCom, buf: &mut [u8]) -> MessageKind<'static> {
+        let size = com.tcp.send_buffer().take_tracked(buf).unwrap();
+        // skip over the frame size
+        let message = Message::create_from_buf(&mut BufferReader::new(&buf[2..size])).unwrap();
+        match message.kind {
+            MessageKind::Error {
+                ms,
+                msg_num,
+                err,
+                msg,
+                loc,
+                stack,
+            } => MessageKind::Error {
+                ms,
+                msg_num,
+                err,
+                msg: Cow::Owned(msg.into_owned()),
+                loc: Cow::Owned(loc.into_owned()),
+                stack: Cow::Owned(stack.into_owned()),
+            },
+            MessageKind::Warning {
+                ms,
+                msg_num,
+                warn,
+                msg,
+                loc,
+                stack,
+            } => MessageKind::Warning {
+                ms,
+                msg_num,
+                warn,
+                msg: Cow::Owned(msg.into_owned()),
+                loc: Cow::Owned(loc.into_owned()),
+                stack: Cow::Owned(stack.into_owned()),
+            },
+            MessageKind::Message { ms, msg_num, msg } => MessageKind::Message {
+                ms,
+                msg_num,
+                msg: Cow::Owned(msg.into_owned()),
+            },
+            MessageKind::VersionInfo { kind } => MessageKind::VersionInfo {
+                kind: match kind {
+                    VersionInfo::LibCVersion(str) => {
+                        VersionInfo::LibCVersion(str.into_owned().into())
+                    }
+                    VersionInfo::ImageVersion(str) => {
+                        VersionInfo::ImageVersion(str.into_owned().into())
+                    }
+                    VersionInfo::Empty(str) => VersionInfo::Empty(str.into_owned().into()),
+                    VersionInfo::CANTalon(idk, can_id) => VersionInfo::CANTalon(idk, can_id),
+                    VersionInfo::PDP(idk, can_id) => VersionInfo::PDP(idk, can_id),
+                    VersionInfo::PCM(idk, can_id) => VersionInfo::PCM(idk, can_id),
+                },
+            },
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    #[test]
+    pub fn messages_round_trip() {
+        let com = RoborioCom::default();
+        let mut buf = vec![0u8; u16::MAX as usize + 2];
+
+        com.send_error(Errors::Timeout, "boom", "main.rs:1", "stack");
+        com.send_warning(Warnings::LoopTimingError, "slow", "", "");
+        com.send_message("hello");
+        // too big for a single frame so it should get cut down instead of wrapping the lengths
+        com.send_error(Errors::Error, &"a".repeat(70000), "loc", "stack");
+
+        assert!(matches!(
+            take_message(&com, &mut buf),
+            MessageKind::Error { msg_num: 0, err: Errors::Timeout, msg, loc, stack, .. }
+                if msg == "boom" && loc == "main.rs:1" && stack == "stack"
+        ));
+        assert!(matches!(
+            take_message(&com, &mut buf),
+            MessageKind::Warning { msg_num: 1, warn: Warnings::LoopTimingError, msg, .. } if msg == "slow"
+        ));
+        assert!(matches!(
+            take_message(&com, &mut buf),
+            MessageKind::Message { msg_num: 2, msg, .. } if msg == "hello"
+        ));
+        assert!(matches!(
+            take_message(&com, &mut buf),
+            MessageKind::Error { msg_num: 3, msg, loc, stack, .. }
+                if msg.len() > 60000 && loc.is_empty() && stack.is_empty()
+        ));
+    }
+
+    #[test]
+    pub fn version_info_round_trip() {
+        let com = RoborioCom::default();
+        let mut buf = vec![0u8; u16::MAX as usize + 2];
+
+        com.add_version_info(VersionInfo::ImageVersion("FRC_roboRIO_2023_v3.2".into()));
+        com.add_version_info(VersionInfo::PDP(1, 0));
+        com.add_version_info(VersionInfo::PCM(1, 2));
+        com.add_version_info(VersionInfo::CANTalon(1, 3));
+        com.send_version_info(&com.get_version_info());
+
+        for expected in com.get_version_info() {
+            match take_message(&com, &mut buf) {
+                MessageKind::VersionInfo { kind } => assert_eq!(kind, expected),
+                other => panic!("unexpected message {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    pub fn observers_get_filtered_frames() {
+        let com = RoborioCom::default();
+        com.set_tcp_role_filter(TcpConnectionRole::Observer, TcpFrameFilter::Errors);
+        *com.common.driverstation_ip.lock() = Some(Ipv4Addr::LOCALHOST.into());
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let mut clients = Vec::new();
+        for id in 0..2 {
+            clients.push(TcpStream::connect(listener.local_addr().unwrap()).unwrap());
+            let (stream, _) = listener.accept().unwrap();
+            let mut connections = com.tcp.connections();
+            connections.push(TcpConnection::new(id, stream).unwrap());
+            assign_roles(&mut connections, Some(Ipv4Addr::LOCALHOST.into()));
+        }
+
+        com.send_message("hello");
+        com.send_warning(Warnings::LoopTimingError, "slow", "", "");
+        com.send_error(Errors::Timeout, "boom", "", "");
+        com.send_disable_faults(1, 2);
+        com.fill_connection_queues(&mut vec![0u8; u16::MAX as usize + 2], None);
+
+        // both are from the driverstation ip but only the newest one controls
+        let stats = com.get_tcp_connections();
+        assert_eq!(stats[0].role, TcpConnectionRole::Observer);
+        assert_eq!(stats[0].queued_frames, 1);
+        assert_eq!(stats[1].role, TcpConnectionRole::Driverstation);
+        assert_eq!(stats[1].queued_frames, 4);
+
+        assert!(com.kick_tcp_connection(0));
+        assert!(!com.kick_tcp_connection(0));
+        assert_eq!(com.get_tcp_connections().len(), 1);
+    }
+
+    #[test]
+    pub fn stalled_observers_dont_block() {
+        let mut com = RoborioCom::default();
+        com.tcp.queue_policy = TcpQueuePolicy::Block;
+        com.tcp.queue_capacity = 1024;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let mut clients = Vec::new();
+        for id in 0..2 {
+            clients.push(TcpStream::connect(listener.local_addr().unwrap()).unwrap());
+            let (stream, _) = listener.accept().unwrap();
+            let mut connections = com.tcp.connections();
+            connections.push(TcpConnection::new(id, stream).unwrap());
+            assign_roles(&mut connections, Some(Ipv4Addr::LOCALHOST.into()));
+        }
+        // the observer has a full queue it isn't reading
+        com.tcp.connections()[0].push(&[0; 1024], 1024);
+
+        com.send_message("hello");
+        com.send_message("world");
+        com.fill_connection_queues(&mut vec![0u8; u16::MAX as usize + 2], None);
+
+        assert!(com.tcp.send_buffer().is_empty());
+        let stats = com.get_tcp_connections();
+        assert_eq!(stats[0].role, TcpConnectionRole::Observer);
+        assert_eq!(stats[0].queued_frames, 2);
+        assert_eq!(stats[1].role, TcpConnectionRole::Driverstation);
+        assert_eq!(stats[1].queued_frames, 2);
+    }
+}
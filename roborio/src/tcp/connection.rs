@@ -0,0 +1,260 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    net::{IpAddr, Shutdown, SocketAddr, TcpStream},
+    time::Duration,
+};
+
+/// What a connection does when its outbound queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TcpQueuePolicy {
+    /// Throw out the oldest queued frames to make room, a slow connection just misses data
+    #[default]
+    DropOldest,
+    /// Stop taking frames out of the shared send buffer untill the driverstation connection has room again.
+    /// If that fills up its oldest frames get dropped instead. Observers always drop their oldest frames
+    Block,
+}
+
+/// What a tcp connection is allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TcpConnectionRole {
+    /// The newest connection from the driverstation we get udp packets from, the only one we read from
+    Driverstation,
+    /// Anything else, like a dashboard or a laptop tailing the console. Nothing it sends is read
+    Observer,
+}
+
+/// Which of the frames we send a connection gets, see [`crate::RoborioCom::set_tcp_role_filter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TcpFrameFilter {
+    #[default]
+    All,
+    /// Only the console: messages, warnings and errors
+    Console,
+    WarningsAndErrors,
+    Errors,
+}
+
+impl TcpFrameFilter {
+    /// `frame` is a whole frame including its size
+    pub(crate) fn allows(self, frame: &[u8]) -> bool {
+        // size, tag, ms, msg num, the 1, code then the error flag
+        const ERROR_FLAG: usize = 2 + 1 + 4 + 2 + 2 + 4;
+        let tag = frame.get(2).copied();
+        let is_error = || tag == Some(0x0B) && frame.get(ERROR_FLAG) == Some(&1);
+        match self {
+            TcpFrameFilter::All => true,
+            TcpFrameFilter::Console => matches!(tag, Some(0x0B | 0x0C)),
+            TcpFrameFilter::WarningsAndErrors => tag == Some(0x0B),
+            TcpFrameFilter::Errors => is_error(),
+        }
+    }
+}
+
+/// The filter each role gets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TcpRoleFilters {
+    pub(crate) driverstation: TcpFrameFilter,
+    pub(crate) observer: TcpFrameFilter,
+}
+
+impl Default for TcpRoleFilters {
+    fn default() -> Self {
+        Self {
+            driverstation: TcpFrameFilter::All,
+            observer: TcpFrameFilter::Console,
+        }
+    }
+}
+
+impl TcpRoleFilters {
+    pub(crate) fn get_mut(&mut self, role: TcpConnectionRole) -> &mut TcpFrameFilter {
+        match role {
+            TcpConnectionRole::Driverstation => &mut self.driverstation,
+            TcpConnectionRole::Observer => &mut self.observer,
+        }
+    }
+
+    pub(crate) fn get(mut self, role: TcpConnectionRole) -> TcpFrameFilter {
+        *self.get_mut(role)
+    }
+}
+
+/// The newest connection from the driverstation gets to control us, everything else only observes
+pub(crate) fn assign_roles(connections: &mut [TcpConnection], driverstation_ip: Option<IpAddr>) {
+    let controlling = connections
+        .iter()
+        .rev()
+        .find(|connection| Some(connection.addr.ip()) == driverstation_ip)
+        .map(|connection| connection.id);
+    for connection in connections {
+        connection.role = if Some(connection.id) == controlling {
+            TcpConnectionRole::Driverstation
+        } else {
+            TcpConnectionRole::Observer
+        };
+    }
+}
+
+/// A snapshot of one tcp connection and its outbound queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpConnectionStats {
+    pub id: u64,
+    pub addr: SocketAddr,
+    pub role: TcpConnectionRole,
+    pub queued_frames: usize,
+    pub queued_bytes: usize,
+    /// frames thrown out because the queue was full
+    pub dropped_frames: usize,
+    pub bytes_sent: usize,
+}
+
+/// How long a single write can block the send thread before we move on to the next connection
+const WRITE_TIMEOUT: Duration = Duration::from_millis(1);
+
+#[derive(Debug)]
+pub(crate) struct TcpConnection {
+    pub(crate) id: u64,
+    pub(crate) addr: SocketAddr,
+    pub(crate) stream: TcpStream,
+    pub(crate) role: TcpConnectionRole,
+    /// whole frames (including their size) waiting to be written
+    queue: VecDeque<Box<[u8]>>,
+    queued_bytes: usize,
+    /// how much of the front frame has already been written
+    front_written: usize,
+    dropped_frames: usize,
+    bytes_sent: usize,
+}
+
+impl TcpConnection {
+    pub(crate) fn new(id: u64, stream: TcpStream) -> io::Result<Self> {
+        let addr = stream.peer_addr()?;
+        // accepted streams can inherit the listeners non blocking mode on some platforms
+        stream.set_nonblocking(false)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        Ok(Self {
+            id,
+            addr,
+            stream,
+            role: TcpConnectionRole::Observer,
+            queue: VecDeque::new(),
+            queued_bytes: 0,
+            front_written: 0,
+            dropped_frames: 0,
+            bytes_sent: 0,
+        })
+    }
+
+    /// An empty queue always has room so a frame bigger than the capacity can't block forever
+    pub(crate) fn has_room(&self, frame_len: usize, capacity: usize) -> bool {
+        self.queue.is_empty() || self.queued_bytes + frame_len <= capacity
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Queue a frame dropping the oldest ones if needed, returns how many were dropped
+    pub(crate) fn push(&mut self, frame: &[u8], capacity: usize) -> usize {
+        // a partially written frame has to be finished or the stream would be garbage
+        let first_droppable = usize::from(self.front_written > 0);
+        let mut dropped = 0;
+        while self.queued_bytes + frame.len() > capacity {
+            match self.queue.remove(first_droppable) {
+                Some(old) => {
+                    self.queued_bytes -= old.len();
+                    dropped += 1;
+                }
+                None => break,
+            }
+        }
+        self.dropped_frames += dropped;
+        self.queued_bytes += frame.len();
+        self.queue.push_back(frame.into());
+        dropped
+    }
+
+    /// Write as much of the queue as the socket will take without blocking for long
+    ///
+    /// Any error means this connection is dead and should be closed
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        while let Some(front) = self.queue.front() {
+            let front_len = front.len();
+            match self.stream.write(&front[self.front_written..]) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.front_written += written;
+                    self.bytes_sent += written;
+                    if self.front_written == front_len {
+                        self.queue.pop_front();
+                        self.queued_bytes -= front_len;
+                        self.front_written = 0;
+                    }
+                }
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(())
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn stats(&self) -> TcpConnectionStats {
+        TcpConnectionStats {
+            id: self.id,
+            addr: self.addr,
+            role: self.role,
+            queued_frames: self.queue.len(),
+            queued_bytes: self.queued_bytes,
+            dropped_frames: self.dropped_frames,
+            bytes_sent: self.bytes_sent,
+        }
+    }
+}
+
+impl Drop for TcpConnection {
+    fn drop(&mut self) {
+        // the receiving thread might hold a clone of this stream, shutting it down makes its reads fail too
+        _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::Read,
+        net::{TcpListener, TcpStream},
+    };
+
+    use super::TcpConnection;
+
+    #[test]
+    pub fn full_queue_drops_oldest() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let mut connection = TcpConnection::new(0, stream).unwrap();
+
+        for i in 0..4u8 {
+            connection.push(&[0, 1, i], 6);
+        }
+        let stats = connection.stats();
+        assert_eq!(stats.queued_frames, 2);
+        assert_eq!(stats.dropped_frames, 2);
+
+        connection.flush().unwrap();
+        let mut buf = [0u8; 6];
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 1, 2, 0, 1, 3]);
+        assert_eq!(connection.stats().queued_bytes, 0);
+    }
+}
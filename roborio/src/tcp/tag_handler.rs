@@ -0,0 +1,234 @@
+use std::{collections::HashMap, sync::Arc};
+
+use num_enum::FromPrimitive;
+use util::{
+    buffer_reader::{BufferReader, BufferReaderError},
+    super_small_vec::SuperSmallVec,
+};
+
+use crate::{event::RoborioEvent, RoborioCom, RoborioComError};
+
+use super::{AxisType, ControllerInfo, JoystickType, MatchInfo, MatchType};
+
+/// Decodes the tcp frames with a specific tag that the driverstation sends us
+///
+/// Register one with [`RoborioCom::set_tcp_tag_handler`], closures with the same signature as
+/// [`TcpTagHandler::handle`] work too
+pub trait TcpTagHandler: Send + Sync {
+    /// `buf` holds everything in the frame after the tag
+    fn handle(
+        &self,
+        com: &RoborioCom,
+        tag: u8,
+        buf: BufferReader<'_>,
+    ) -> Result<(), BufferReaderError>;
+}
+
+impl<F> TcpTagHandler for F
+where
+    F: Fn(&RoborioCom, u8, BufferReader<'_>) -> Result<(), BufferReaderError> + Send + Sync,
+{
+    fn handle(
+        &self,
+        com: &RoborioCom,
+        tag: u8,
+        buf: BufferReader<'_>,
+    ) -> Result<(), BufferReaderError> {
+        self(com, tag, buf)
+    }
+}
+
+/// 0x02, a description of one of the controllers plugged into the driverstation
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ControllerDescriptorHandler;
+
+impl TcpTagHandler for ControllerDescriptorHandler {
+    fn handle(
+        &self,
+        com: &RoborioCom,
+        _tag: u8,
+        mut buf: BufferReader<'_>,
+    ) -> Result<(), BufferReaderError> {
+        let index = buf.read_u8()?;
+        let is_xbox = buf.read_u8()? == 1;
+
+        let c = ControllerInfo {
+            is_xbox,
+            js_type: JoystickType::from_primitive(buf.read_u8()?),
+            name: buf.read_short_str()?.to_owned(),
+            axis: {
+                let mut axis = SuperSmallVec::new();
+                for _ in 0..buf.read_u8()? {
+                    axis.push(AxisType::from_primitive(buf.read_u8()?))
+                }
+                axis
+            },
+            buttons: buf.read_u8()?,
+            povs: buf.read_u8()?,
+        };
+
+        let mut lock = com.tcp.controller_info.lock();
+        if let Some(t) = lock.get_mut(index as usize) {
+            if t.as_ref() != Some(&c) {
+                *t = Some(c.clone());
+                com.tcp
+                    .controller_info_generation
+                    .fetch_add(1, std::sync::atomic::Ordering::Release);
+                drop(lock);
+                com.emit_event(RoborioEvent::ControllerInfo {
+                    index: index as usize,
+                    info: Some(c),
+                });
+            }
+        } else {
+            drop(lock);
+            com.report_error(RoborioComError::TcpInvalidControllerIndex(index));
+        }
+        Ok(())
+    }
+}
+
+/// 0x07, the event name and match the driverstation (or fms) says we are in
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatchInfoHandler;
+
+impl TcpTagHandler for MatchInfoHandler {
+    fn handle(
+        &self,
+        com: &RoborioCom,
+        _tag: u8,
+        mut buf: BufferReader<'_>,
+    ) -> Result<(), BufferReaderError> {
+        let match_info = MatchInfo {
+            name: buf.read_short_str()?.to_owned(),
+            match_type: MatchType::from_primitive(buf.read_u8()?),
+            match_number: buf.read_u16()?,
+            replay: buf.read_u8()?,
+        };
+        let mut lock = com.tcp.match_info.lock();
+        if lock.as_ref() != Some(&match_info) {
+            *lock = Some(match_info.clone());
+            drop(lock);
+            com.emit_event(RoborioEvent::MatchInfo(match_info));
+        }
+        Ok(())
+    }
+}
+
+/// 0x0E, the game specific message, the rest of the frame is the string
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GameDataHandler;
+
+impl TcpTagHandler for GameDataHandler {
+    fn handle(
+        &self,
+        com: &RoborioCom,
+        _tag: u8,
+        mut buf: BufferReader<'_>,
+    ) -> Result<(), BufferReaderError> {
+        let game_data = buf.read_str(buf.remaining_buf_len())?;
+        let mut lock = com.tcp.game_data.lock();
+        if lock.as_deref() != Some(game_data) {
+            *lock = Some(game_data.to_owned());
+            drop(lock);
+            com.emit_event(RoborioEvent::GameData(game_data.to_owned()));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct TagHandlers {
+    handlers: HashMap<u8, Arc<dyn TcpTagHandler>>,
+}
+
+impl TagHandlers {
+    pub(crate) fn get(&self, tag: u8) -> Option<Arc<dyn TcpTagHandler>> {
+        self.handlers.get(&tag).cloned()
+    }
+}
+
+impl Default for TagHandlers {
+    fn default() -> Self {
+        let mut handlers: HashMap<u8, Arc<dyn TcpTagHandler>> = HashMap::new();
+        handlers.insert(0x02, Arc::new(ControllerDescriptorHandler));
+        handlers.insert(0x07, Arc::new(MatchInfoHandler));
+        handlers.insert(0x0E, Arc::new(GameDataHandler));
+        Self { handlers }
+    }
+}
+
+impl std::fmt::Debug for TagHandlers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut tags: Vec<_> = self.handlers.keys().collect();
+        tags.sort();
+        f.debug_struct("TagHandlers").field("tags", &tags).finish()
+    }
+}
+
+impl RoborioCom {
+    /// Decode frames tagged with `tag` using `handler` instead of whatever handled them before
+    ///
+    /// 0x02, 0x07 and 0x0E have handlers by default, frames with tags that have no handler are
+    /// reported as [`RoborioComError::TcpUnknownTag`]. Returns the handler that was replaced
+    pub fn set_tcp_tag_handler(
+        &self,
+        tag: u8,
+        handler: impl TcpTagHandler + 'static,
+    ) -> Option<Arc<dyn TcpTagHandler>> {
+        self.tcp
+            .tag_handlers
+            .write()
+            .handlers
+            .insert(tag, Arc::new(handler))
+    }
+
+    /// Stop decoding frames tagged with `tag`, returns the handler that was removed
+    pub fn remove_tcp_tag_handler(&self, tag: u8) -> Option<Arc<dyn TcpTagHandler>> {
+        self.tcp.tag_handlers.write().handlers.remove(&tag)
+    }
+
+    /// Go back to the handlers a new [`RoborioCom`] has
+    pub fn reset_tcp_tag_handlers(&self) {
+        *self.tcp.tag_handlers.write() = TagHandlers::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    };
+
+    use util::buffer_reader::BufferReader;
+
+    use crate::{event::RoborioEvent, RoborioCom, RoborioComError};
+
+    #[test]
+    pub fn custom_and_unknown_tags() {
+        let com = RoborioCom::default();
+        let events = com.subscribe();
+
+        com.read_data(BufferReader::new(&[0x0E, b'L', b'R', b'L']))
+            .unwrap();
+        assert_eq!(com.get_game_data().as_deref(), Some("LRL"));
+
+        let seen = Arc::new(AtomicU8::new(0));
+        let seen2 = seen.clone();
+        com.set_tcp_tag_handler(0x42, move |_: &RoborioCom, _, mut buf: BufferReader<'_>| {
+            seen2.store(buf.read_u8()?, Ordering::Relaxed);
+            Ok(())
+        });
+        com.read_data(BufferReader::new(&[0x42, 7])).unwrap();
+        assert_eq!(seen.load(Ordering::Relaxed), 7);
+
+        com.remove_tcp_tag_handler(0x42);
+        com.read_data(BufferReader::new(&[0x42, 7])).unwrap();
+
+        assert!(matches!(events.try_recv(), Ok(RoborioEvent::GameData(data)) if data == "LRL"));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(RoborioEvent::Error(err)) if matches!(*err, RoborioComError::TcpUnknownTag(0x42))
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    io,
+    ops::Deref,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use robot_comm::robot_to_driver::{CpuUsage, RobotToDriverDiskUsage, RobotToDriverRamUsage};
+
+use crate::{collector::PeriodicCollector, PossibleRcSelf, RoborioCom, RoborioComError};
+
+/// Where the system telemetry collector reads from and how often
+///
+/// The defaults read the real system once a second
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemTelemetryConfig {
+    /// The directory `stat` and `meminfo` are read from
+    pub proc_root: PathBuf,
+    /// Free space is reported for the filesystem this path is on
+    pub disk_path: PathBuf,
+    pub interval: Duration,
+}
+
+impl Default for SystemTelemetryConfig {
+    fn default() -> Self {
+        Self {
+            proc_root: PathBuf::from("/proc"),
+            disk_path: PathBuf::from("/"),
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The jiffies a cpu has spent in each state, the columns of a `cpuN` line in `/proc/stat`
+#[derive(Debug, Default, Clone, Copy)]
+struct CpuTimes {
+    user: u64,
+    system: u64,
+    total: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct SystemTelemetry {
+    config: SystemTelemetryConfig,
+    /// what each cpu looked like last time we sampled it, the first sample is relative to boot
+    last_cpu_times: Vec<CpuTimes>,
+}
+
+impl SystemTelemetry {
+    pub(crate) fn new(config: SystemTelemetryConfig) -> Self {
+        Self {
+            config,
+            last_cpu_times: Vec::new(),
+        }
+    }
+
+    fn cpu_usage(&mut self) -> io::Result<Vec<CpuUsage>> {
+        let stat = std::fs::read_to_string(self.config.proc_root.join("stat"))?;
+
+        let mut times = Vec::new();
+        for line in stat.lines() {
+            let mut columns = line.split_ascii_whitespace();
+            // the plain `cpu` line is every cpu added together
+            match columns.next() {
+                Some(name) if name.starts_with("cpu") && name.len() > 3 => {}
+                _ => continue,
+            }
+            let columns = columns
+                .map(|column| column.parse::<u64>().map_err(invalid_data))
+                .collect::<io::Result<Vec<_>>>()?;
+            // user nice system idle iowait irq softirq steal ...
+            let column = |i: usize| columns.get(i).copied().unwrap_or(0);
+            times.push(CpuTimes {
+                user: column(0) + column(1),
+                system: column(2) + column(5) + column(6),
+                total: columns.iter().take(8).sum(),
+            });
+        }
+
+        if self.last_cpu_times.len() != times.len() {
+            self.last_cpu_times = vec![CpuTimes::default(); times.len()];
+        }
+
+        let usage = times
+            .iter()
+            .zip(&self.last_cpu_times)
+            .map(|(now, last)| {
+                let total = now.total.saturating_sub(last.total);
+                let percent = |now: u64, last: u64| {
+                    if total == 0 {
+                        0.0
+                    } else {
+                        now.saturating_sub(last) as f32 * 100.0 / total as f32
+                    }
+                };
+                CpuUsage::new(
+                    percent(now.user, last.user),
+                    0.0,
+                    0.0,
+                    percent(now.system, last.system),
+                )
+            })
+            .collect();
+        self.last_cpu_times = times;
+        Ok(usage)
+    }
+
+    fn ram_usage(&self) -> io::Result<RobotToDriverRamUsage> {
+        let meminfo = std::fs::read_to_string(self.config.proc_root.join("meminfo"))?;
+
+        let field = |name: &str| {
+            meminfo.lines().find_map(|line| {
+                let value = line.strip_prefix(name)?.strip_prefix(':')?;
+                value
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            })
+        };
+        // older kernels dont have MemAvailable
+        let kib = field("MemAvailable")
+            .or_else(|| field("MemFree"))
+            .ok_or_else(|| invalid_data("meminfo has no MemAvailable or MemFree"))?;
+        Ok(RobotToDriverRamUsage {
+            bytes_free: kib * 1024,
+        })
+    }
+
+    fn disk_usage(&self) -> io::Result<RobotToDriverDiskUsage> {
+        Ok(RobotToDriverDiskUsage {
+            bytes_free: disk_bytes_free(&self.config.disk_path)?,
+        })
+    }
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(unix)]
+fn disk_bytes_free(path: &Path) -> io::Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn disk_bytes_free(_path: &Path) -> io::Result<u64> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+impl PeriodicCollector for SystemTelemetry {
+    type Config = SystemTelemetryConfig;
+
+    fn from_config(config: SystemTelemetryConfig) -> Self {
+        Self::new(config)
+    }
+
+    fn config(&self) -> &SystemTelemetryConfig {
+        &self.config
+    }
+
+    fn interval(&self) -> Duration {
+        self.config.interval
+    }
+
+    fn collect(&mut self, com: &RoborioCom) {
+        com.collect_system_telemetry(self)
+    }
+}
+
+impl RoborioCom {
+    /// Start (or with `None` stop) filling the cpu, ram and disk usage tags from the system
+    ///
+    /// This only does anything while the daemon is running. The collector overwrites anything set with
+    /// [`RoborioCom::set_cpu_usage`] and friends, after it stops the last values it read are kept
+    pub fn set_system_telemetry(&self, config: Option<SystemTelemetryConfig>) {
+        *self.common.telemetry.lock() = config;
+    }
+
+    pub fn get_system_telemetry(&self) -> Option<SystemTelemetryConfig> {
+        self.common.telemetry.lock().clone()
+    }
+
+    pub(super) fn run_telemetry_daemon<T: PossibleRcSelf + Deref<Target = Self>>(myself: &T) {
+        Self::run_collector_daemon::<SystemTelemetry, _>(myself, |com| {
+            com.common.telemetry.lock().clone()
+        })
+    }
+
+    pub(crate) fn collect_system_telemetry(&self, telemetry: &mut SystemTelemetry) {
+        match telemetry.cpu_usage() {
+            Ok(usage) => self.set_cpu_usage(Some(&usage)),
+            Err(err) => self.report_error(RoborioComError::SystemTelemetryError(err)),
+        }
+        match telemetry.ram_usage() {
+            Ok(usage) => self.set_ram_usage(Some(usage)),
+            Err(err) => self.report_error(RoborioComError::SystemTelemetryError(err)),
+        }
+        match telemetry.disk_usage() {
+            Ok(usage) => self.set_disk_usage(Some(usage)),
+            Err(err) => self.report_error(RoborioComError::SystemTelemetryError(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use robot_comm::robot_to_driver::{CpuUsage, RobotToDriverRamUsage};
+
+    use super::{SystemTelemetry, SystemTelemetryConfig};
+    use crate::{test::TempPath, RoborioCom};
+
+    #[test]
+    pub fn reads_procfs_fixtures() {
+        let root = TempPath::new("telemetry");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("meminfo"),
+            "MemTotal:        1000 kB\nMemFree:          100 kB\nMemAvailable:     500 kB\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("stat"),
+            "cpu  100 0 50 1850 0 0 0 0 0 0\ncpu0 100 0 50 850 0 0 0 0 0 0\ncpu1 0 0 0 1000 0 0 0 0 0 0\nintr 0\n",
+        )
+        .unwrap();
+
+        let com = RoborioCom::default();
+        let _ = com.set_error_handler(|_, err| panic!("{err:?}"));
+        let mut telemetry = SystemTelemetry::new(SystemTelemetryConfig {
+            proc_root: root.to_path_buf(),
+            disk_path: root.to_path_buf(),
+            ..Default::default()
+        });
+
+        com.collect_system_telemetry(&mut telemetry);
+        assert_eq!(
+            com.get_ram_usage(),
+            Some(RobotToDriverRamUsage {
+                bytes_free: 500 * 1024
+            })
+        );
+        assert!(com.get_disk_usage().is_some());
+
+        std::fs::write(
+            root.join("stat"),
+            "cpu  200 0 100 2000 0 0 0 0 0 0\ncpu0 200 0 100 900 0 0 0 0 0 0\ncpu1 0 0 0 1100 0 0 0 0 0 0\n",
+        )
+        .unwrap();
+        com.collect_system_telemetry(&mut telemetry);
+        assert_eq!(
+            com.get_cpu_usage().as_deref(),
+            Some(&[CpuUsage::new(50.0, 0.0, 0.0, 25.0), CpuUsage::default()][..])
+        );
+    }
+}
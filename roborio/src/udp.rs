@@ -20,7 +20,7 @@ use robot_comm::{
         DriverstationToRobotCorePacketDate,
     },
     robot_to_driver::{
-        self, writter::RobotToDriverstaionPacketWritter, CpuUsage, PdpPortReport, PdpPowerReport,
+        self, writter::RobotToDriverstaionPacketWritter, CpuUsage, PdpPowerReport, PdpReport,
         RobotToDriverCanUsage, RobotToDriverDiskUsage, RobotToDriverRamUsage, RobotToDriverRumble,
         RobotToDriverstationPacket,
     },
@@ -31,14 +31,18 @@ use util::{
     buffer_writter::{BufferWritter, SliceBufferWritter},
 };
 
-use crate::{PossibleRcSelf, RoborioCom, RoborioComError};
+use crate::{
+    event::RoborioEvent, input::InputTracker, link_stats::LinkTracker, match_timer::MatchTracker,
+    recording::RecordedKind, wall_clock::WallClock, PossibleRcSelf, RoborioCom, RoborioComError,
+};
 
 #[derive(Debug)]
 pub(super) struct RoborioUdp {
-    recv: Mutex<DriverstationToRobotCorePacketDate>,
+    pub(crate) recv: Mutex<DriverstationToRobotCorePacketDate>,
     joystick_values: Mutex<[Option<Joystick>; 6]>,
     countdown: Mutex<Option<f32>>,
     time: Mutex<TimeData>,
+    pub(crate) wall_clock: Mutex<WallClock>,
     observed_information: Mutex<RobotToDriverstationPacket>,
     clear_observed_status_on_send: AtomicBool,
 
@@ -54,9 +58,12 @@ pub(super) struct RoborioUdp {
     /// The number of packets being -received- that have been "dropped"
     /// (if the sequence skips a value)
     packets_dropped: AtomicUsize,
+    pub(crate) link_stats: Mutex<LinkTracker>,
+    pub(crate) match_tracker: Mutex<MatchTracker>,
+    pub(crate) input: Mutex<InputTracker>,
 
-    connection_disable_timeout_ms: AtomicU32,
-    connection_reset_timeout_ms: AtomicU32,
+    pub(crate) connection_disable_timeout_ms: AtomicU32,
+    pub(crate) connection_reset_timeout_ms: AtomicU32,
 
     hooks: RwLock<Hooks>,
 }
@@ -91,6 +98,7 @@ impl Default for RoborioUdp {
             joystick_values: Default::default(),
             countdown: Default::default(),
             time: Default::default(),
+            wall_clock: Default::default(),
             observed_information: Default::default(),
             clear_observed_status_on_send: Default::default(),
             tag_data: Default::default(),
@@ -102,6 +110,9 @@ impl Default for RoborioUdp {
             bytes_received: Default::default(),
             packets_received: Default::default(),
             packets_dropped: Default::default(),
+            link_stats: Default::default(),
+            match_tracker: Default::default(),
+            input: Default::default(),
             //mid
             connection_disable_timeout_ms: AtomicU32::new(120),
             connection_reset_timeout_ms: AtomicU32::new(20000),
@@ -113,11 +124,12 @@ impl Default for RoborioUdp {
 
 #[derive(Default, Debug)]
 struct RoborioUdpTags {
-    rumble: Option<RobotToDriverRumble>,
+    /// outputs for each controller, see [`RobotToDriverRumble`]
+    rumble: [RobotToDriverRumble; 6],
     disk_usage: Option<RobotToDriverDiskUsage>,
     cpu_usage: Option<Vec<CpuUsage>>,
     ram_usage: Option<RobotToDriverRamUsage>,
-    pdp_port_report: Option<PdpPortReport>,
+    pdp_report: Option<PdpReport>,
     pdp_power_report: Option<PdpPowerReport>,
     can_usage: Option<RobotToDriverCanUsage>,
 }
@@ -127,7 +139,7 @@ struct RoborioUdpTagFrequencies {
     disk_usage_m: AtomicU8,
     cpu_usage_m: AtomicU8,
     ram_usage_m: AtomicU8,
-    pdp_port_report_m: AtomicU8,
+    pdp_report_m: AtomicU8,
     pdp_power_report_m: AtomicU8,
     can_usage_m: AtomicU8,
 }
@@ -138,7 +150,7 @@ impl Default for RoborioUdpTagFrequencies {
             disk_usage_m: AtomicU8::new(51),
             cpu_usage_m: AtomicU8::new(51),
             ram_usage_m: AtomicU8::new(51),
-            pdp_port_report_m: AtomicU8::new(3),
+            pdp_report_m: AtomicU8::new(3),
             pdp_power_report_m: AtomicU8::new(3),
             can_usage_m: AtomicU8::new(3),
         }
@@ -147,22 +159,38 @@ impl Default for RoborioUdpTagFrequencies {
 
 struct UdpTagAcceptor<'a> {
     daemon: &'a RoborioCom,
+    sequence: u16,
+    received_at: std::time::Instant,
+    /// the countdown in this packet, stays `None` if the tags couldn't be read
+    countdown: &'a mut Option<f32>,
 }
 impl<'a> PacketTagAcceptor for UdpTagAcceptor<'a> {
     #[inline(always)]
     fn accept_joystick(&mut self, index: usize, joystick: Option<Joystick>) {
         if let Some(joy) = self.daemon.udp.joystick_values.lock().get_mut(index) {
-            *joy = joystick;
+            let old = std::mem::replace(joy, joystick);
+            self.daemon.udp.input.lock().record(
+                index,
+                self.sequence,
+                old.as_ref(),
+                joystick.as_ref(),
+            );
         }
     }
 
     #[inline(always)]
     fn accept_countdown(&mut self, countdown: Option<f32>) {
+        *self.countdown = countdown;
         *self.daemon.udp.countdown.lock() = countdown;
     }
     #[inline(always)]
     fn accept_time_data(&mut self, timedata: TimeData) {
+        // this is called for every packet, only stop asking once the time actually shows up
+        if !timedata.has_data() {
+            return;
+        }
         self.daemon.udp.time.lock().update_existing_from(&timedata);
+        self.daemon.update_wall_clock(&timedata, self.received_at);
         self.daemon
             .udp
             .observed_information
@@ -188,15 +216,17 @@ impl RoborioCom {
             }
             if reset_kind >= 2 {
                 // reset out udp information every time reconnect
-                myself.udp.connected.store(false, Relaxed);
+                myself.set_udp_connected(false);
                 myself.common.driverstation_ip.lock().take();
                 myself.udp.packets_dropped.store(0, Relaxed);
                 myself.udp.bytes_received.store(0, Relaxed);
                 myself.udp.packets_received.store(0, Relaxed);
                 myself.udp.packets_sent.store(0, Relaxed);
                 myself.udp.bytes_sent.store(0, Relaxed);
+                *myself.udp.link_stats.lock() = LinkTracker::default();
                 *myself.udp.time.lock() = TimeData::default();
                 *myself.udp.joystick_values.lock() = [None; 6];
+                myself.udp.input.lock().reset();
                 {
                     // these two states percist across reconnects
                     let mut lock = myself.udp.observed_information.lock();
@@ -210,12 +240,13 @@ impl RoborioCom {
                     lock.status.set_is_roborio(true);
                 }
                 *myself.udp.countdown.lock() = None;
+                myself.udp.match_tracker.lock().reset();
             }
 
-            // we should accept from any adress on port 1110
+            // by default we should accept from any adress on port 1110
+            let addrs = myself.common.addrs;
             let socket =
-                match UdpSocket::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 1110))
-                {
+                match UdpSocket::bind(SocketAddr::new(addrs.bind_ip, addrs.udp_receive_port)) {
                     Ok(ok) => ok,
                     Err(err) => {
                         myself.report_error(RoborioComError::UdpIoInitError(err));
@@ -238,6 +269,11 @@ impl RoborioCom {
             // this runs until we need to reconnect or exit the daemon
             myself.run_udp_daemon_inner(myself, socket);
         }
+
+        // the daemon is stopping so dont leave anything thinking we're still enabled/connected
+        myself.set_udp_connected(false);
+        myself.common.driverstation_ip.lock().take();
+        myself.force_disable();
     }
 
     fn run_udp_daemon_inner<T: 'static + Send + PossibleRcSelf + Deref<Target = Self>>(
@@ -258,6 +294,7 @@ impl RoborioCom {
             }
             match socket.recv_from(&mut recv_buf) {
                 Ok((read, rec_addr)) => {
+                    let received_at = std::time::Instant::now();
                     self.udp.bytes_received.fetch_add(read, Relaxed);
                     let recv_buf = &recv_buf[..read];
 
@@ -280,35 +317,14 @@ impl RoborioCom {
                         *self.common.driverstation_ip.lock() = Some(send_addr);
                     }
 
-                    let mut reader = BufferReader::new(recv_buf);
-                    match DriverToRobotPacketReader::new(&mut reader) {
-                        Ok((recv_packet, reader)) => {
-                            self.respond_to_udp_packet(
-                                &mut send_buf,
-                                &socket,
-                                send_addr,
-                                recv_packet,
-                            );
-
-                            // if we've got this far yipee!!
-                            self.udp.packets_received.fetch_add(1, Relaxed);
-
-                            // read the additional tags and extra data after because it could possibly be slow
-                            if let Err(err) = reader.read_tags(UdpTagAcceptor { daemon: self }) {
-                                //waaaaa!
-                                self.report_error(RoborioComError::UdpPacketTagReadError(err))
-                            }
-
-                            if recv_packet.request_code.is_requesting_lib_info() {
-                                self.common.request_info.store(true, Relaxed);
-                            }
-
-                            last_sucsess = std::time::Instant::now()
-                        }
-                        Err(err) => {
-                            self.udp.connected.store(false, Relaxed);
-                            self.report_error(RoborioComError::UdpCorePacketReadError(err));
-                        }
+                    self.record_packet(RecordedKind::Udp, received_at, recv_buf);
+                    if self.handle_udp_datagram(
+                        recv_buf,
+                        received_at,
+                        &mut send_buf,
+                        Some((&socket, send_addr)),
+                    ) {
+                        last_sucsess = std::time::Instant::now()
                     }
                 }
                 Err(err) => {
@@ -319,7 +335,7 @@ impl RoborioCom {
                     {
                         self.force_disable();
                         self.report_error(RoborioComError::UdpConnectionTimeoutError);
-                        self.udp.connected.store(false, Relaxed);
+                        self.set_udp_connected(false);
                         self.udp.reset_con.store(2, Relaxed);
                         break;
                     }
@@ -330,7 +346,7 @@ impl RoborioCom {
                     } else {
                         // if theres an IO error something weird happened so reset the connection
                         self.udp.reset_con.store(2, Relaxed);
-                        self.udp.connected.store(false, Relaxed);
+                        self.set_udp_connected(false);
                         self.report_error(RoborioComError::UdpIoReceiveError(err));
                         return;
                     }
@@ -339,8 +355,64 @@ impl RoborioCom {
         }
     }
 
+    /// Parse and respond to one datagram from the driverstation, returns true if it was a valid packet
+    ///
+    /// Without a socket to respond with (when replaying) everything but actually sending the response still happens
+    pub(crate) fn handle_udp_datagram(
+        &self,
+        recv_buf: &[u8],
+        received_at: std::time::Instant,
+        send_buf: &mut [u8],
+        response: Option<(&UdpSocket, IpAddr)>,
+    ) -> bool {
+        use std::sync::atomic::Ordering::Relaxed;
+
+        let mut reader = BufferReader::new(recv_buf);
+        match DriverToRobotPacketReader::new(&mut reader) {
+            Ok((recv_packet, reader)) => {
+                self.udp.link_stats.lock().record_received(
+                    received_at,
+                    recv_packet.sequence,
+                    recv_buf.len(),
+                );
+                self.respond_to_udp_packet(send_buf, response, recv_packet);
+
+                // if we've got this far yipee!!
+                self.udp.packets_received.fetch_add(1, Relaxed);
+
+                // read the additional tags and extra data after because it could possibly be slow
+                let mut countdown = None;
+                if let Err(err) = reader.read_tags(UdpTagAcceptor {
+                    daemon: self,
+                    sequence: recv_packet.sequence,
+                    received_at,
+                    countdown: &mut countdown,
+                }) {
+                    //waaaaa!
+                    self.report_error(RoborioComError::UdpPacketTagReadError(err))
+                }
+                // only the countdown from this packet, an old one would re-anchor the timer every packet
+                self.udp.match_tracker.lock().record(
+                    recv_packet.control_code,
+                    countdown,
+                    received_at,
+                );
+
+                if recv_packet.request_code.is_requesting_lib_info() {
+                    self.common.request_info.store(true, Relaxed);
+                }
+                true
+            }
+            Err(err) => {
+                // one bad datagram isn't a disconnect, thats left to the timeout and io errors
+                self.report_error(RoborioComError::UdpCorePacketReadError(err));
+                false
+            }
+        }
+    }
+
     #[cold]
-    fn force_disable(&self) {
+    pub(crate) fn force_disable(&self) {
         let mut obv_lock = self.udp.observed_information.lock();
         let mut recv_lock = self.udp.recv.lock();
         let old = obv_lock.control_code;
@@ -353,11 +425,26 @@ impl RoborioCom {
         let new = obv_lock.control_code;
         drop(obv_lock);
         drop(recv_lock);
-        self.run_hooks(
-            old,
-            new,
-            RobotRequestCode::new(), /*Empty request code*/
-        );
+        let request = self.udp.recv.lock().request_code;
+        // the request didn't change so no restart hooks run
+        self.run_hooks(old, new, request, request);
+    }
+
+    pub(crate) fn set_udp_connected(&self, connected: bool) {
+        use std::sync::atomic::Ordering::Relaxed;
+        if self.udp.connected.swap(connected, Relaxed) != connected {
+            if connected {
+                // keep the wall clock right after every reconnect
+                self.request_time();
+            }
+            if let Some(ip) = *self.common.driverstation_ip.lock() {
+                self.emit_event(if connected {
+                    RoborioEvent::DriverstationConnected(ip)
+                } else {
+                    RoborioEvent::DriverstationDisconnected(ip)
+                });
+            }
+        }
     }
 
     #[cold]
@@ -374,19 +461,33 @@ impl RoborioCom {
     }
 
     #[cold]
-    fn run_hooks(&self, old: ControlCode, new: ControlCode, request: RobotRequestCode) {
+    fn run_hooks(
+        &self,
+        old: ControlCode,
+        new: ControlCode,
+        old_request: RobotRequestCode,
+        request: RobotRequestCode,
+    ) {
+        if old != new {
+            self.emit_event(RoborioEvent::ModeChanged { old, new });
+        }
+
         if !old.is_estop() && new.is_estop() {
+            self.emit_event(RoborioEvent::Estop);
             self.run_estop_hook()
         }
 
-        if request.should_restart_roborio() {
+        // the driverstation keeps the request set for a while so only act when it first shows up
+        if !old_request.should_restart_roborio() && request.should_restart_roborio() {
+            self.emit_event(RoborioEvent::RestartRioRequested);
             if let Some(hook) = &self.udp.hooks.read().restart_rio_hook {
                 // if this panics were f**ked so very hard
                 hook()
             }
         }
 
-        if request.should_restart_roborio_code() {
+        if !old_request.should_restart_roborio_code() && request.should_restart_roborio_code() {
+            self.emit_event(RoborioEvent::RestartCodeRequested);
             if let Some(hook) = &self.udp.hooks.read().restart_code_hook {
                 let res = std::panic::catch_unwind(hook);
                 // if we panic here do a -not so gracful- process abort~
@@ -408,7 +509,9 @@ impl RoborioCom {
                 if let Some($hook) = &self.udp.hooks.read().$hook {
                     let res = std::panic::catch_unwind($hook);
                     if let Err(err) = res {
-                        self.report_error(RoborioComError::ModeSwitchHookPanic(err));
+                        self.report_error(RoborioComError::ModeSwitchHookPanic(
+                            crate::panic_message(&*err),
+                        ));
                         self.run_estop_hook();
                     }
                 }
@@ -416,6 +519,8 @@ impl RoborioCom {
         }
 
         if !old.is_disabled() && new.is_disabled() {
+            // this also covers the driverstation timing out since that force disables us
+            self.stop_all_motors();
             mode_switch_hook!(disable_hook);
         // the mode can be teleop/auton/test even when disabled
         // so the if else ensures that we dont run each respective hook
@@ -433,12 +538,11 @@ impl RoborioCom {
     fn respond_to_udp_packet(
         &self,
         send_buf: &mut [u8],
-        socket: &UdpSocket,
-        send_addr: IpAddr,
+        response: Option<(&UdpSocket, IpAddr)>,
         recv_packet: DriverstationToRobotCorePacketDate,
     ) {
         use std::sync::atomic::Ordering::Relaxed;
-        *self.udp.recv.lock() = recv_packet;
+        let old_request = std::mem::replace(&mut *self.udp.recv.lock(), recv_packet).request_code;
 
         let mut packet = self.udp.observed_information.lock();
 
@@ -491,7 +595,7 @@ impl RoborioCom {
                     // If we faild to write the core packet (this really should never fail but whatever)
                     // just stop tryint to respons.
                     // we cant return because we need to handle our hooks so we break
-                    self.udp.connected.store(false, Relaxed);
+                    self.set_udp_connected(false);
                     self.report_error(RoborioComError::UdpCorePacketWriteError(err));
                     break 'response;
                 }
@@ -499,22 +603,41 @@ impl RoborioCom {
 
             self.write_udp_packet_tags(&mut packet_writter);
 
+            let Some((socket, send_addr)) = response else {
+                // replaying, theres nobody to respond to
+                self.set_udp_connected(true);
+                break 'response;
+            };
+
             // actually send our response
-            match socket.send_to(packet_writter.into_buf(), SocketAddr::new(send_addr, 1150)) {
+            match socket.send_to(
+                packet_writter.into_buf(),
+                SocketAddr::new(send_addr, self.common.addrs.udp_send_port),
+            ) {
                 Ok(wrote) => {
                     self.udp.bytes_sent.fetch_add(wrote, Relaxed);
                     self.udp.packets_sent.fetch_add(1, Relaxed);
-                    self.udp.connected.store(true, Relaxed);
+                    self.udp
+                        .link_stats
+                        .lock()
+                        .record_sent(std::time::Instant::now(), wrote);
+                    self.set_udp_connected(true);
                 }
                 Err(err) => {
-                    self.udp.connected.store(false, Relaxed);
+                    self.set_udp_connected(false);
                     self.report_error(RoborioComError::UdpIoSendError(err))
                 }
             }
         }
 
         // TODO: we could potentially speed up/optimize by checking for a change in the controlcode/request code before we call this?
-        self.run_hooks(old_control_code, new_control_code, recv_packet.request_code);
+        self.run_hooks(
+            old_control_code,
+            new_control_code,
+            old_request,
+            recv_packet.request_code,
+        );
+        self.check_motor_safety();
     }
 
     /// Write the tags to the response packet writter acording the current settings/data in outself
@@ -529,14 +652,6 @@ impl RoborioCom {
             let packets_sent = self.udp.packets_sent.load(Relaxed);
 
             macro_rules! write_tag {
-                ($tag:ident) => {
-                    if let Some($tag) = lock.$tag{
-                        if let Err(err) = packet_writter.$tag($tag){
-                            self.report_error(RoborioComError::UdpPacketTagWritterError(err));
-                            break 'tags;
-                        }
-                    }
-                };
                 ($tag:ident, $tag_m:ident $(, $offset:expr)?) => {
                     if let Some($tag) = lock.$tag{
                         let $tag_m = self.udp.tag_frequences.$tag_m.load(Relaxed);
@@ -565,11 +680,22 @@ impl RoborioCom {
                 };
             }
 
-            write_tag!(rumble);
+            // the tags have no index so send every controller up to the last one with outputs set
+            let controllers = lock
+                .rumble
+                .iter()
+                .rposition(|rumble| *rumble != RobotToDriverRumble::default())
+                .map_or(0, |last| last + 1);
+            for rumble in &lock.rumble[..controllers] {
+                if let Err(err) = packet_writter.rumble(*rumble) {
+                    self.report_error(RoborioComError::UdpPacketTagWritterError(err));
+                    break 'tags;
+                }
+            }
             write_tag!(disk_usage, disk_usage_m, 1);
             write_tag!(&cpu_usage, cpu_usage_m, 2);
             write_tag!(ram_usage, ram_usage_m, 3);
-            write_tag!(&pdp_port_report, pdp_port_report_m, 4);
+            write_tag!(&pdp_report, pdp_report_m, 4);
             write_tag!(pdp_power_report, pdp_power_report_m, 5);
             write_tag!(can_usage, can_usage_m, 6);
         }
@@ -852,12 +978,46 @@ impl RoborioCom {
 
     //------------------------------ tags
 
-    pub fn set_rumble(&self, rumble: Option<RobotToDriverRumble>) {
-        self.udp.tag_data.lock().rumble = rumble;
+    /// Set the HID outputs and rumble of a controller on the driverstation at once
+    pub fn set_joystick_outputs(&self, controller: u8, outputs: RobotToDriverRumble) {
+        if let Some(rumble) = self.udp.tag_data.lock().rumble.get_mut(controller as usize) {
+            *rumble = outputs;
+        }
     }
 
-    pub fn get_rumble(&self) -> Option<RobotToDriverRumble> {
-        self.udp.tag_data.lock().rumble
+    pub fn get_joystick_outputs(&self, controller: u8) -> Option<RobotToDriverRumble> {
+        self.udp
+            .tag_data
+            .lock()
+            .rumble
+            .get(controller as usize)
+            .copied()
+    }
+
+    /// Set a single HID output, `output` starts at 1 like WPILib
+    pub fn set_joystick_output(&self, controller: u8, output: u8, value: bool) {
+        let Some(bit) = output
+            .checked_sub(1)
+            .and_then(|bit| 1u32.checked_shl(bit.into()))
+        else {
+            return;
+        };
+        if let Some(rumble) = self.udp.tag_data.lock().rumble.get_mut(controller as usize) {
+            if value {
+                rumble.outputs |= bit;
+            } else {
+                rumble.outputs &= !bit;
+            }
+        }
+    }
+
+    /// Set the rumble of a controller, `left` and `right` are clamped to [0.0, 1.0]
+    pub fn set_rumble(&self, controller: u8, left: f32, right: f32) {
+        let scale = |val: f32| (val.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+        if let Some(rumble) = self.udp.tag_data.lock().rumble.get_mut(controller as usize) {
+            rumble.left = scale(left);
+            rumble.right = scale(right);
+        }
     }
 
     pub fn set_disk_usage(&self, usage: Option<RobotToDriverDiskUsage>) {
@@ -869,12 +1029,13 @@ impl RoborioCom {
     }
 
     pub fn set_cpu_usage(&self, usage: Option<&[CpuUsage]>) {
-        if let Some(old_usage) = &mut self.udp.tag_data.lock().cpu_usage {
-            old_usage.clear();
-            if let Some(usage) = usage {
-                old_usage.reserve(usage.len());
-                old_usage.copy_from_slice(usage);
+        let mut lock = self.udp.tag_data.lock();
+        match (usage, &mut lock.cpu_usage) {
+            (Some(usage), Some(old_usage)) => {
+                old_usage.clear();
+                old_usage.extend_from_slice(usage);
             }
+            (usage, old_usage) => *old_usage = usage.map(<[CpuUsage]>::to_vec),
         }
     }
 
@@ -890,12 +1051,12 @@ impl RoborioCom {
         self.udp.tag_data.lock().ram_usage
     }
 
-    pub fn set_pdp_port_report(&self, report: Option<PdpPortReport>) {
-        self.udp.tag_data.lock().pdp_port_report = report;
+    pub fn set_pdp_report(&self, report: Option<PdpReport>) {
+        self.udp.tag_data.lock().pdp_report = report;
     }
 
-    pub fn get_pdp_port_report(&self) -> Option<PdpPortReport> {
-        self.udp.tag_data.lock().pdp_port_report
+    pub fn get_pdp_report(&self) -> Option<PdpReport> {
+        self.udp.tag_data.lock().pdp_report
     }
 
     pub fn set_pdp_power_report(&self, report: Option<PdpPowerReport>) {
@@ -988,9 +1149,9 @@ generate_udp_tag_data_frequencies_impl!(
     get_ram_usage_frequency,
     set_ram_usage_frequency,
     ram_usage_m,
-    get_pdp_port_report_frequency,
-    set_pdp_port_report_frequency,
-    pdp_port_report_m,
+    get_pdp_report_frequency,
+    set_pdp_report_frequency,
+    pdp_report_m,
     get_pdp_power_report_frequency,
     set_pdp_power_report_frequency,
     pdp_power_report_m,
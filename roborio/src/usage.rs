@@ -0,0 +1,84 @@
+use net_comm::robot_to_driverstation::{usage::UsageEntry, Message};
+use util::team_number::TeamNumber;
+
+pub use net_comm::robot_to_driverstation::usage::{instances, ResourceType};
+
+use crate::RoborioCom;
+
+/// Everything that has been reported with [`RoborioCom::report_usage`]
+///
+/// The driverstation only gets sent the whole list again after something new is reported
+#[derive(Debug, Default)]
+pub(crate) struct UsageReports {
+    entries: Vec<UsageEntry<'static>>,
+    dirty: bool,
+}
+
+// everything but the actual entries in a 0x01 frame
+const USAGE_REPORT_HEADER: usize = 1 + 2 + 1;
+
+impl RoborioCom {
+    /// Report that some resource/feature is being used (the same as `HAL_Report` in WPILib)
+    ///
+    /// Reporting the same resource and instance again replaces the old context and feature
+    pub fn report_usage(&self, resource: ResourceType, instance: u16, context: u16, feature: &str) {
+        // features are sent as short strings
+        let mut end = feature.len().min(u8::MAX as usize);
+        while !feature.is_char_boundary(end) {
+            end -= 1;
+        }
+        let entry = UsageEntry::new(resource, instance, context, feature[..end].to_owned());
+
+        let mut usage = self.common.usage.lock();
+        if let Some(old) = usage
+            .entries
+            .iter_mut()
+            .find(|old| old.resource == resource && old.instance == instance)
+        {
+            if *old == entry {
+                return;
+            }
+            *old = entry;
+        } else {
+            usage.entries.push(entry);
+        }
+        usage.dirty = true;
+    }
+
+    pub fn get_usage_reports(&self) -> Vec<UsageEntry<'static>> {
+        self.common.usage.lock().entries.clone()
+    }
+
+    /// Send every reported usage entry to the driverstation now instead of waiting for the daemon to batch them
+    pub fn send_usage_report(&self) {
+        //0x01
+        let mut usage = self.common.usage.lock();
+        usage.dirty = false;
+        let entries = usage.entries.clone();
+        drop(usage);
+
+        let team = self.common.team_number.unwrap_or(TeamNumber(0));
+
+        // everything has to fit in a single u16 sized frame so split it up if theres a lot
+        let mut batch = Vec::new();
+        let mut batch_len = USAGE_REPORT_HEADER;
+        for entry in entries {
+            if batch_len + entry.encoded_len() > u16::MAX as usize {
+                self.send_tcp_message(Message::usage_report(team, std::mem::take(&mut batch)));
+                batch_len = USAGE_REPORT_HEADER;
+            }
+            batch_len += entry.encoded_len();
+            batch.push(entry);
+        }
+        if !batch.is_empty() {
+            self.send_tcp_message(Message::usage_report(team, batch));
+        }
+    }
+
+    /// Called by the daemon, only sends a report if something new was reported since the last one
+    pub(crate) fn send_usage_report_if_changed(&self) {
+        if self.common.usage.lock().dirty {
+            self.send_usage_report();
+        }
+    }
+}
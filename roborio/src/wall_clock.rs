@@ -0,0 +1,135 @@
+use std::{io, time::Instant};
+
+use chrono::{DateTime, NaiveDateTime, TimeDelta, TimeZone};
+use chrono_tz::Tz;
+use robot_comm::common::time_data::TimeData;
+
+use crate::{RoborioCom, RoborioComError};
+
+#[derive(Debug, Default)]
+pub(crate) struct WallClock {
+    /// the utc time the driverstation last sent and when we got it
+    anchor: Option<(NaiveDateTime, Instant)>,
+    time_zone: Option<Tz>,
+    sync_system_clock: bool,
+}
+
+impl WallClock {
+    fn at(&self, now: Instant) -> Option<DateTime<Tz>> {
+        let (time, received_at) = self.anchor?;
+        let elapsed = TimeDelta::from_std(now.saturating_duration_since(received_at)).ok()?;
+        let utc = time.checked_add_signed(elapsed)?;
+        Some(self.time_zone.unwrap_or(Tz::UTC).from_utc_datetime(&utc))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_system_clock(time: DateTime<Tz>) -> io::Result<()> {
+    let spec = libc::timespec {
+        tv_sec: time.timestamp() as libc::time_t,
+        tv_nsec: time.timestamp_subsec_nanos() as _,
+    };
+    if unsafe { libc::clock_settime(libc::CLOCK_REALTIME, &spec) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_system_clock(_: DateTime<Tz>) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+impl RoborioCom {
+    /// Anchor the wall clock to time data that came in at `received_at`, the driverstation only sends
+    /// it after we ask (which happens every time it connects)
+    pub(crate) fn update_wall_clock(&self, time_data: &TimeData, received_at: Instant) {
+        let mut clock = self.udp.wall_clock.lock();
+        if let Some(time_zone) = time_data.get_time_zone() {
+            clock.time_zone = Some(time_zone);
+        }
+        let Some(time) = time_data.get_time() else {
+            return;
+        };
+        clock.anchor = Some((time, received_at));
+        let now = clock.at(Instant::now()).filter(|_| clock.sync_system_clock);
+        drop(clock);
+
+        if let Some(now) = now {
+            if let Err(err) = set_system_clock(now) {
+                self.report_error(RoborioComError::SystemClockError(err));
+            }
+        }
+    }
+
+    /// The driverstations time in its time zone (utc untill it sends one), `None` untill it sends the time
+    ///
+    /// The time is only sent when we ask for it so between packets (and after the driverstation disconnects)
+    /// this keeps advancing with a monotonic clock from the last time we got
+    pub fn wall_clock(&self) -> Option<DateTime<Tz>> {
+        self.udp.wall_clock.lock().at(Instant::now())
+    }
+
+    /// Set the system clock (linux only) every time the driverstation sends us the time, this needs
+    /// `CAP_SYS_TIME` (or root). Off by default
+    pub fn set_sync_system_clock(&self, sync: bool) {
+        self.udp.wall_clock.lock().sync_system_clock = sync;
+    }
+
+    pub fn get_sync_system_clock(&self) -> bool {
+        self.udp.wall_clock.lock().sync_system_clock
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use chrono::{NaiveDate, TimeDelta};
+    use chrono_tz::Tz;
+
+    use crate::RoborioCom;
+
+    /// A driverstation udp packet with the date and time zone tags
+    fn packet_with_time(time_zone: &str) -> Vec<u8> {
+        let mut packet = vec![0, 2, 1, 0, 0, 0];
+        // 2024-03-09 18:30:15.000250 utc, months count from 0 and years from 1900
+        packet.extend_from_slice(&[11, 15, 0, 0, 0, 250, 15, 30, 18, 9, 2, 124]);
+        packet.push(1 + time_zone.len() as u8);
+        packet.push(16);
+        packet.extend_from_slice(time_zone.as_bytes());
+        packet
+    }
+
+    #[test]
+    pub fn wall_clock_advances_from_driverstation_time() {
+        let com = RoborioCom::default();
+        assert_eq!(com.wall_clock(), None);
+
+        let mut send_buf = [0u8; 1024];
+        // connecting asks for the time
+        assert!(com.handle_udp_datagram(&[0, 1, 1, 0, 0, 0], Instant::now(), &mut send_buf, None));
+        assert!(com.get_request_time());
+
+        let received_at = Instant::now();
+        assert!(com.handle_udp_datagram(
+            &packet_with_time("America/Chicago"),
+            received_at,
+            &mut send_buf,
+            None
+        ));
+        assert!(!com.get_request_time());
+
+        let sent = NaiveDate::from_ymd_opt(2024, 3, 9)
+            .unwrap()
+            .and_hms_micro_opt(18, 30, 15, 250)
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        let now = com.wall_clock().unwrap();
+        assert_eq!(now.timezone(), Tz::America__Chicago);
+        let advanced = now.naive_utc() - sent;
+        assert!(advanced >= TimeDelta::milliseconds(20));
+        assert!(advanced <= TimeDelta::from_std(received_at.elapsed()).unwrap());
+    }
+}
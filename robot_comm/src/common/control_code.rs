@@ -48,6 +48,10 @@ impl ControlCode {
         self.get(Self::BROWN_OUT_PROTECTION)
     }
 
+    pub fn is_fms_attached(&self) -> bool {
+        self.get(Self::FMS_ATTACHED)
+    }
+
     pub fn set_driverstation_attached(&mut self, arg: bool) {
         self.set(Self::STOP_CONNECTION, arg);
     }
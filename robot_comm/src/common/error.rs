@@ -19,6 +19,7 @@ pub enum RobotPacketParseError {
     // robot to driver
     RobotToDriverInvalidCommVersion(u8),
     RobotToDriverInvalidUsageTag(u8),
+    RobotToDriverInvalidPdpReportLength(usize),
     InvalidTimeZoneData,
     InvalidTimeData,
 }
@@ -62,6 +62,15 @@ impl TimeData {
         Ok(())
     }
 
+    /// The date and time the driverstation sent, in utc
+    pub fn get_time(&self) -> Option<NaiveDateTime> {
+        self.time
+    }
+
+    pub fn get_time_zone(&self) -> Option<Tz> {
+        self.time_zone
+    }
+
     pub fn get_system_time(&self) -> Option<SystemTime> {
         let date = self.time?;
         let t = std::time::Duration::new(date.timestamp() as u64, date.timestamp_subsec_nanos());
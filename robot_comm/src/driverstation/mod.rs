@@ -5,7 +5,7 @@ use std::{
 };
 
 use util::{
-    buffer_reader::{BufferReader, ReadFromBuf},
+    buffer_reader::BufferReader,
     buffer_writter::{BufferWritter, SliceBufferWritter, WriteToBuff},
     robot_voltage::RobotVoltage,
     socket::Socket,
@@ -13,11 +13,16 @@ use util::{
 
 use crate::{
     common::{
-        alliance_station::AllianceStation, control_code::ControlCode, joystick::Joystick,
-        request_code::RobotRequestCode, roborio_status_code::RobotStatusCode, time_data::TimeData,
+        alliance_station::AllianceStation, control_code::ControlCode, error::RobotPacketParseError,
+        joystick::Joystick, request_code::RobotRequestCode, roborio_status_code::RobotStatusCode,
+        time_data::TimeData,
     },
     driver_to_robot::DriverstationToRobotPacket,
-    robot_to_driver::{reader::print_packet, RobotToDriverstationPacket},
+    robot_to_driver::{
+        reader::{print_packet, PacketTagAcceptor, RobotToDriverPacketReader},
+        CpuUsage, PdpPowerReport, PdpReport, RobotToDriverCanUsage, RobotToDriverDiskUsage,
+        RobotToDriverRamUsage, RobotToDriverRumble,
+    },
 };
 
 pub struct RobotComm {
@@ -36,6 +41,43 @@ struct OtherData {
     observed_voltage: RobotVoltage,
     observed_state: RobotStatusCode,
     observed_control: ControlCode,
+    joystick_outputs: [RobotToDriverRumble; 6],
+    pdp_report: Option<PdpReport>,
+    pdp_power_report: Option<PdpPowerReport>,
+}
+
+/// Collects the joystick outputs and pdp reports out of a robot packets tags
+#[derive(Default)]
+struct JoystickOutputsAcceptor {
+    outputs: [RobotToDriverRumble; 6],
+    next: usize,
+    pdp_report: Option<PdpReport>,
+    pdp_power_report: Option<PdpPowerReport>,
+}
+
+impl PacketTagAcceptor for JoystickOutputsAcceptor {
+    fn accept_rumble(&mut self, rumble: RobotToDriverRumble) {
+        if let Some(outputs) = self.outputs.get_mut(self.next) {
+            *outputs = rumble;
+        }
+        self.next += 1;
+    }
+
+    fn accept_ram_usage(&mut self, _bytes_free: RobotToDriverRamUsage) {}
+
+    fn accept_disk_usage(&mut self, _bytes_free: RobotToDriverDiskUsage) {}
+
+    fn accept_cpu_usage(&mut self, _cpu_usage: &[CpuUsage]) {}
+
+    fn accept_can_usage(&mut self, _can_usafe: RobotToDriverCanUsage) {}
+
+    fn accept_pdp_report(&mut self, pdp_report: PdpReport) {
+        self.pdp_report = Some(pdp_report);
+    }
+
+    fn accept_pdp_power_report(&mut self, pdp_power_report: PdpPowerReport) {
+        self.pdp_power_report = Some(pdp_power_report);
+    }
 }
 
 impl RobotComm {
@@ -96,8 +138,6 @@ impl RobotComm {
             return;
         };
 
-        let mut packet = RobotToDriverstationPacket::default();
-
         let mut buf = [0u8; 4069];
         let mut request_time = false;
 
@@ -179,11 +219,17 @@ impl RobotComm {
                     // let res = socket.read_into(&mut packet, &mut buf);
                     let res = socket.read_with(&mut buf, |read| {
                         // let _ = print_packet(read); // print the packet for diagnostics
-                        packet.read_into_from_buf(&mut BufferReader::new(read))
+                        let (packet, tags) =
+                            RobotToDriverPacketReader::new(BufferReader::new(read)).read_core()?;
+                        let mut outputs = JoystickOutputsAcceptor::default();
+                        // tags we cant read shouldn't make us throw out the whole packet
+                        // the outputs read before a bad tag are still good
+                        _ = tags.read_tags(&mut outputs);
+                        Ok::<_, RobotPacketParseError>((packet, outputs))
                     });
                     let time = start.elapsed();
 
-                    if let Ok(Some(packet)) = res {
+                    if let Ok(Some((packet, tag_data))) = res {
                         request_time = packet.request.request_time();
 
                         let mut other_lock = self.other_data.lock().unwrap();
@@ -202,6 +248,14 @@ impl RobotComm {
 
                         other_lock.observed_voltage = packet.battery;
                         other_lock.observed_state = packet.status;
+                        other_lock.joystick_outputs = tag_data.outputs;
+                        // the reports aren't sent every packet so keep the last one around
+                        if tag_data.pdp_report.is_some() {
+                            other_lock.pdp_report = tag_data.pdp_report;
+                        }
+                        if tag_data.pdp_power_report.is_some() {
+                            other_lock.pdp_power_report = tag_data.pdp_power_report;
+                        }
 
                         if packet.sequence < packet_sent_sqeu && packet.sequence != 0 {
                             println!(
@@ -293,6 +347,27 @@ impl RobotComm {
         self.other_data.lock().unwrap().observed_voltage
     }
 
+    /// The HID outputs and rumble the robot last asked for on the controller at `index`
+    pub fn get_joystick_outputs(&self, index: usize) -> RobotToDriverRumble {
+        self.other_data
+            .lock()
+            .unwrap()
+            .joystick_outputs
+            .get(index)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The last per channel pdp report the robot sent
+    pub fn get_pdp_report(&self) -> Option<PdpReport> {
+        self.other_data.lock().unwrap().pdp_report
+    }
+
+    /// The last total current, power and energy report the robot sent
+    pub fn get_pdp_power_report(&self) -> Option<PdpPowerReport> {
+        self.other_data.lock().unwrap().pdp_power_report
+    }
+
     pub fn update_joystick(&self, index: usize, joystick: Joystick) {
         self.packet_data
             .lock()
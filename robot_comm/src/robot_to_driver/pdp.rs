@@ -0,0 +1,338 @@
+use util::{
+    buffer_reader::{BufferReader, CreateFromBuf, ReadFromBuf},
+    buffer_writter::{BufferWritter, BufferWritterError, WriteToBuff},
+};
+
+use crate::common::error::RobotPacketParseError;
+
+// the tags are the CTRE PDP CAN status frames passed straight through so the same scaling applies
+const AMPS_PER_UNIT: f32 = 0.125;
+const WATTS_PER_UNIT: f32 = 0.125;
+const VOLTS_PER_UNIT: f32 = 0.05;
+const VOLTS_OFFSET: f32 = 4.0;
+const DEGREES_PER_UNIT: f32 = 1.032_508_4;
+const DEGREES_OFFSET: f32 = -67.856_45;
+
+/// The power distribution module a [`PdpReport`] came from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PdpModule {
+    /// CTRE Power Distribution Panel
+    #[default]
+    Ctre,
+    /// REV Power Distribution Hub
+    Rev,
+}
+
+impl PdpModule {
+    /// The number of channels the module has, and so how many currents are sent
+    pub const fn channels(self) -> usize {
+        match self {
+            PdpModule::Ctre => 16,
+            PdpModule::Rev => 24,
+        }
+    }
+
+    const fn channel_bytes(self) -> usize {
+        let channels = self.channels();
+        (channels / 6) * group_bytes(6) + group_bytes(channels % 6)
+    }
+
+    fn from_channel_bytes(len: usize) -> Option<Self> {
+        [PdpModule::Ctre, PdpModule::Rev]
+            .into_iter()
+            .find(|module| module.channel_bytes() == len)
+    }
+}
+
+/// Per channel currents and the state of the module (tag 0x08)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdpReport {
+    pub module: PdpModule,
+    /// Always zero so probably the CAN id of the module
+    pub can_id: u8,
+    /// Current through each channel in amps, only the first [`PdpModule::channels`] are sent
+    ///
+    /// Sent with a resolution of 0.125A in the range [0, 127.875]
+    pub currents: [f32; PdpReport::MAX_CHANNELS],
+    /// Internal resistance of the battery in milliohms
+    pub battery_resistance: u8,
+    /// Sent with a resolution of 0.05V in the range [4.0, 16.75]
+    pub bus_voltage: f32,
+    /// Temperature of the module in degrees celsius
+    pub temperature: f32,
+}
+
+impl Default for PdpReport {
+    fn default() -> Self {
+        Self {
+            module: PdpModule::default(),
+            can_id: 0,
+            currents: [0.0; Self::MAX_CHANNELS],
+            battery_resistance: 0,
+            bus_voltage: VOLTS_OFFSET,
+            temperature: 0.0,
+        }
+    }
+}
+
+impl PdpReport {
+    pub const MAX_CHANNELS: usize = 24;
+
+    pub fn new(module: PdpModule) -> Self {
+        Self {
+            module,
+            ..Default::default()
+        }
+    }
+
+    /// The currents of the channels this module actually has
+    pub fn channel_currents(&self) -> &[f32] {
+        &self.currents[..self.module.channels()]
+    }
+
+    /// The sum of every channels current in amps
+    pub fn total_current(&self) -> f32 {
+        self.channel_currents().iter().sum()
+    }
+
+    /// The length of the tag without its size and id
+    pub const fn encoded_len(&self) -> usize {
+        1 + self.module.channel_bytes() + 3
+    }
+}
+
+impl<'a> ReadFromBuf<'a> for PdpReport {
+    type Error = RobotPacketParseError;
+
+    /// Reads the rest of the tag after its id, the module is inferred from its length
+    fn read_into_from_buf(&mut self, buf: &mut BufferReader<'a>) -> Result<&mut Self, Self::Error> {
+        let len = buf.remaining_buf_len();
+        self.module = len
+            .checked_sub(1 + 3)
+            .and_then(PdpModule::from_channel_bytes)
+            .ok_or(RobotPacketParseError::RobotToDriverInvalidPdpReportLength(
+                len,
+            ))?;
+        self.can_id = buf.read_u8()?;
+
+        let channels = self.module.channels();
+        for currents in self.currents[..channels].chunks_mut(6) {
+            let len = group_bytes(currents.len());
+            let mut bytes = [0u8; 8];
+            bytes[..len].copy_from_slice(buf.read_amount(len)?);
+            let mut packed = u64::from_be_bytes(bytes);
+            for current in currents.iter_mut() {
+                *current = (packed >> 54) as f32 * AMPS_PER_UNIT;
+                packed <<= 10;
+            }
+        }
+        self.currents[channels..].fill(0.0);
+
+        self.battery_resistance = buf.read_u8()?;
+        self.bus_voltage = buf.read_u8()? as f32 * VOLTS_PER_UNIT + VOLTS_OFFSET;
+        self.temperature = buf.read_u8()? as f32 * DEGREES_PER_UNIT + DEGREES_OFFSET;
+        Ok(self)
+    }
+}
+
+impl<'a> CreateFromBuf<'a> for PdpReport {
+    fn create_from_buf(buf: &mut BufferReader<'a>) -> Result<Self, Self::Error> {
+        let mut report = Self::default();
+        report.read_into_from_buf(buf)?;
+        Ok(report)
+    }
+}
+
+impl<'a> WriteToBuff<'a> for PdpReport {
+    type Error = BufferWritterError;
+
+    fn write_to_buf<T: BufferWritter<'a>>(&self, buf: &mut T) -> Result<(), Self::Error> {
+        buf.write_u8(self.can_id)?;
+
+        for currents in self.channel_currents().chunks(6) {
+            let mut packed = 0u64;
+            for (i, current) in currents.iter().enumerate() {
+                packed |= quantize(*current, 0.0, AMPS_PER_UNIT, 0x3FF) << (54 - i * 10);
+            }
+            let len = group_bytes(currents.len());
+            buf.write_buf(&packed.to_be_bytes()[..len])?;
+        }
+
+        buf.write_u8(self.battery_resistance)?;
+        buf.write_u8(quantize(self.bus_voltage, VOLTS_OFFSET, VOLTS_PER_UNIT, 0xFF) as u8)?;
+        buf.write_u8(quantize(self.temperature, DEGREES_OFFSET, DEGREES_PER_UNIT, 0xFF) as u8)?;
+        Ok(())
+    }
+}
+
+/// Total current, power and energy of the whole module (tag 0x09)
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PdpPowerReport {
+    /// Always zero so probably the CAN id of the module
+    pub can_id: u8,
+    /// How long the module measures for between reports, normally 20ms
+    pub period_ms: u8,
+    /// Sent with a resolution of 0.125A in the range [0, 511.875]
+    pub total_current: f32,
+    /// Sent with a resolution of 0.125W in the range [0, 8191.875]
+    pub power: f32,
+    /// Energy used since the module powered on in joules
+    ///
+    /// Sent with a resolution of 0.125W over [`PdpPowerReport::period_ms`]
+    pub energy: f64,
+}
+
+impl PdpPowerReport {
+    /// The length of the tag without its size and id
+    pub const ENCODED_LEN: usize = 9;
+
+    fn joules_per_unit(&self) -> f64 {
+        WATTS_PER_UNIT as f64 * self.period_ms as f64 / 1000.0
+    }
+}
+
+impl<'a> ReadFromBuf<'a> for PdpPowerReport {
+    type Error = RobotPacketParseError;
+
+    fn read_into_from_buf(&mut self, buf: &mut BufferReader<'a>) -> Result<&mut Self, Self::Error> {
+        self.can_id = buf.read_u8()?;
+        self.period_ms = buf.read_u8()?;
+        // 12 bits current, 16 bits power, 28 bits energy
+        let mut packed = [0u8; 8];
+        packed[1..].copy_from_slice(buf.read_const_amount::<7>()?);
+        let packed = u64::from_be_bytes(packed);
+        self.total_current = (packed >> 44) as f32 * AMPS_PER_UNIT;
+        self.power = ((packed >> 28) & 0xFFFF) as f32 * WATTS_PER_UNIT;
+        self.energy = (packed & 0x0FFF_FFFF) as f64 * self.joules_per_unit();
+        Ok(self)
+    }
+}
+
+impl<'a> CreateFromBuf<'a> for PdpPowerReport {
+    fn create_from_buf(buf: &mut BufferReader<'a>) -> Result<Self, Self::Error> {
+        let mut report = Self::default();
+        report.read_into_from_buf(buf)?;
+        Ok(report)
+    }
+}
+
+impl<'a> WriteToBuff<'a> for PdpPowerReport {
+    type Error = BufferWritterError;
+
+    fn write_to_buf<T: BufferWritter<'a>>(&self, buf: &mut T) -> Result<(), Self::Error> {
+        buf.write_u8(self.can_id)?;
+        buf.write_u8(self.period_ms)?;
+        let energy = (self.energy / self.joules_per_unit()).round() as u64;
+        let packed = quantize(self.total_current, 0.0, AMPS_PER_UNIT, 0xFFF) << 44
+            | quantize(self.power, 0.0, WATTS_PER_UNIT, 0xFFFF) << 28
+            | energy.min(0x0FFF_FFFF);
+        buf.write_buf(&packed.to_be_bytes()[1..])?;
+        Ok(())
+    }
+}
+
+// channels are packed 6 to a u64 with 4 bits of padding, the last partial group is only as long as it needs to be
+const fn group_bytes(channels: usize) -> usize {
+    if channels == 6 {
+        8
+    } else {
+        (channels * 10).div_ceil(8)
+    }
+}
+
+fn quantize(val: f32, offset: f32, per_unit: f32, max: u64) -> u64 {
+    // float to int casts saturate and NaN becomes 0
+    (((val - offset) / per_unit).round() as u64).min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use util::{
+        buffer_reader::{BufferReader, CreateFromBuf},
+        buffer_writter::{BufferWritter, SliceBufferWritter, WriteToBuff},
+    };
+
+    use super::{PdpModule, PdpPowerReport, PdpReport};
+
+    fn round_trip<T: for<'a> CreateFromBuf<'a> + for<'a> WriteToBuff<'a>>(val: &T) -> (T, usize)
+    where
+        for<'a> <T as WriteToBuff<'a>>::Error: std::fmt::Debug,
+        for<'a> <T as util::buffer_reader::ReadFromBuf<'a>>::Error: std::fmt::Debug,
+    {
+        let mut buf = [0u8; 64];
+        let mut bufw = SliceBufferWritter::new(&mut buf);
+        val.write_to_buf(&mut bufw).unwrap();
+        let len = bufw.curr_buf_len();
+        let mut bufr = BufferReader::new(bufw.curr_buf());
+        let read = T::create_from_buf(&mut bufr).unwrap();
+        bufr.assert_empty().unwrap();
+        (read, len)
+    }
+
+    #[test]
+    pub fn pdp_report_round_trip() {
+        for module in [PdpModule::Ctre, PdpModule::Rev] {
+            let mut report = PdpReport::new(module);
+            report.battery_resistance = 17;
+            report.bus_voltage = 12.35;
+            report.temperature = 30.0;
+            for (i, current) in report.currents[..module.channels()].iter_mut().enumerate() {
+                *current = i as f32 * 5.125;
+            }
+            let (read, len) = round_trip(&report);
+            assert_eq!(len, report.encoded_len());
+            assert_eq!(read.module, module);
+            assert_eq!(read.currents, report.currents);
+            assert_eq!(read.battery_resistance, 17);
+            assert!((read.bus_voltage - 12.35).abs() < 0.025);
+            assert!((read.temperature - 30.0).abs() < 0.52);
+
+            // once quantized it should survive any number of trips
+            assert_eq!(round_trip(&read).0, read);
+        }
+        assert_eq!(PdpReport::new(PdpModule::Ctre).encoded_len(), 25);
+        assert_eq!(PdpReport::new(PdpModule::Rev).encoded_len(), 36);
+    }
+
+    #[test]
+    pub fn pdp_report_matches_wire() {
+        // channel 0 and 15 at 1A, everything else off
+        let mut wire = [0u8; 25];
+        wire[1] = 0b0000_0010;
+        wire[21] = 0b0000_1000;
+        wire[22] = 0xFF;
+        wire[23] = 171;
+        wire[24] = 85;
+        let report = PdpReport::create_from_buf(&mut BufferReader::new(&wire)).unwrap();
+        assert_eq!(report.module, PdpModule::Ctre);
+        assert_eq!(report.currents[0], 1.0);
+        assert_eq!(report.currents[15], 1.0);
+        assert_eq!(report.total_current(), 2.0);
+
+        let mut buf = [0u8; 25];
+        let mut bufw = SliceBufferWritter::new(&mut buf);
+        report.write_to_buf(&mut bufw).unwrap();
+        assert_eq!(bufw.curr_buf(), &wire);
+
+        assert!(PdpReport::create_from_buf(&mut BufferReader::new(&wire[..24])).is_err());
+    }
+
+    #[test]
+    pub fn pdp_power_report_round_trip() {
+        let report = PdpPowerReport {
+            can_id: 0,
+            period_ms: 20,
+            total_current: 118.0,
+            power: 1416.5,
+            energy: 42.5,
+        };
+        let (read, len) = round_trip(&report);
+        assert_eq!(len, PdpPowerReport::ENCODED_LEN);
+        assert_eq!(read, report);
+
+        let wire = [0, 20, 3, 176, 44, 17, 58, 254, 223];
+        let read = PdpPowerReport::create_from_buf(&mut BufferReader::new(&wire)).unwrap();
+        assert_eq!(read.period_ms, 20);
+        assert_eq!(round_trip(&read).0, read);
+    }
+}
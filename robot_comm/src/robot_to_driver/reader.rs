@@ -2,13 +2,10 @@ use std::marker::PhantomData;
 
 use util::buffer_reader::{BufferReader, CreateFromBuf, ReadFromBuf};
 
-use crate::{
-    common::error::RobotPacketParseError,
-    robot_to_driver::{PdpPowerReportInner, RobotToDriverCanUsage},
-};
+use crate::{common::error::RobotPacketParseError, robot_to_driver::RobotToDriverCanUsage};
 
 use super::{
-    CpuUsage, PdpPortAmpReport, PdpPortReport, RobotToDriverDiskUsage, RobotToDriverRamUsage,
+    CpuUsage, PdpPowerReport, PdpReport, RobotToDriverDiskUsage, RobotToDriverRamUsage,
     RobotToDriverRumble, RobotToDriverstationPacket,
 };
 
@@ -17,8 +14,10 @@ pub struct RobotToDriverPacketReader<'a, T> {
     phantom: PhantomData<T>,
 }
 
-struct Core;
-struct Tags;
+/// The reader hasn't read the core packet yet
+pub struct Core;
+/// The core packet has been read and only the tags are left
+pub struct Tags;
 
 impl<'a> RobotToDriverPacketReader<'a, Core> {
     pub fn new(reader: BufferReader<'a>) -> Self {
@@ -84,12 +83,12 @@ pub fn print_packet(buf: &[u8]) -> Result<(), RobotPacketParseError> {
         }
 
         #[inline(always)]
-        fn accept_pdp_port_report(&mut self, pdp_port_report: PdpPortReport) {
-            println!("{:#?}", pdp_port_report);
+        fn accept_pdp_report(&mut self, pdp_report: PdpReport) {
+            println!("{:#?}", pdp_report);
         }
 
         #[inline(always)]
-        fn accept_pdp_power_report(&mut self, pdp_power_report: PdpPowerReportInner<[u8; 9]>) {
+        fn accept_pdp_power_report(&mut self, pdp_power_report: PdpPowerReport) {
             println!("{:#?}", pdp_power_report);
         }
     }
@@ -122,15 +121,16 @@ impl<'a> RobotToDriverPacketReader<'a, Tags> {
             let extra_id = buf.read_u8()?;
             match extra_id {
                 1 => {
-                    todo!("Actually implement the joystick receiver");
-                    // let bruh = buf.read_u32()?;
-                    // let left = buf.read_u16()?;
-                    // let right = buf.read_u16()?;
-                    // acceptor.accept_rumble(RobotToDriverRumble { left, right });
+                    let rumble = RobotToDriverRumble {
+                        outputs: buf.read_u32()?,
+                        left: buf.read_u16()?,
+                        right: buf.read_u16()?,
+                    };
+                    acceptor.accept_rumble(rumble);
                 }
                 4 => {
                     let bytes_free = buf.read_u64()?;
-                    acceptor.accept_ram_usage(RobotToDriverRamUsage { bytes_free });
+                    acceptor.accept_disk_usage(RobotToDriverDiskUsage { bytes_free });
                 }
                 5 => {
                     let cpus = buf.read_u8()? as usize;
@@ -141,20 +141,13 @@ impl<'a> RobotToDriverPacketReader<'a, Tags> {
                 }
                 6 => {
                     let bytes_free = buf.read_u64()?;
-                    acceptor.accept_disk_usage(RobotToDriverDiskUsage { bytes_free });
+                    acceptor.accept_ram_usage(RobotToDriverRamUsage { bytes_free });
                 }
                 8 => {
-                    let val = PdpPortReport {
-                        unknown_0: buf.read_u8()?,
-                        port_amp_report: PdpPortAmpReport(*buf.read_const_amount::<21>()?),
-                        unknown_1: *buf.read_const_amount::<3>()?,
-                    };
-                    acceptor.accept_pdp_port_report(val);
+                    acceptor.accept_pdp_report(PdpReport::create_from_buf(&mut buf)?);
                 }
                 9 => {
-                    let vals = buf.read_const_amount::<9>()?;
-                    let data = PdpPowerReportInner(*vals);
-                    acceptor.accept_pdp_power_report(data);
+                    acceptor.accept_pdp_power_report(PdpPowerReport::create_from_buf(&mut buf)?);
                 }
                 14 => {
                     let utilization = RobotToDriverCanUsage {
@@ -175,11 +168,182 @@ impl<'a> RobotToDriverPacketReader<'a, Tags> {
 }
 
 pub trait PacketTagAcceptor {
+    /// Called once per controller in order, the first call is for controller 0
     fn accept_rumble(&mut self, rumble: RobotToDriverRumble);
     fn accept_ram_usage(&mut self, bytes_free: RobotToDriverRamUsage);
     fn accept_disk_usage(&mut self, bytes_free: RobotToDriverDiskUsage);
     fn accept_cpu_usage(&mut self, cpu_usage: &[CpuUsage]);
     fn accept_can_usage(&mut self, can_usafe: RobotToDriverCanUsage);
-    fn accept_pdp_port_report(&mut self, pdp_port_report: PdpPortReport);
-    fn accept_pdp_power_report(&mut self, pdp_power_report: PdpPowerReportInner<[u8; 9]>);
+    fn accept_pdp_report(&mut self, pdp_report: PdpReport);
+    fn accept_pdp_power_report(&mut self, pdp_power_report: PdpPowerReport);
+}
+
+#[cfg(test)]
+mod test {
+    use util::{buffer_reader::BufferReader, buffer_writter::SliceBufferWritter};
+
+    use super::{PacketTagAcceptor, RobotToDriverPacketReader};
+    use crate::robot_to_driver::{
+        writter::RobotToDriverstaionPacketWritter, CpuUsage, PdpModule, PdpPowerReport, PdpReport,
+        RobotToDriverCanUsage, RobotToDriverDiskUsage, RobotToDriverRamUsage, RobotToDriverRumble,
+        RobotToDriverstationPacket,
+    };
+
+    #[derive(Default)]
+    struct Collected {
+        rumbles: Vec<RobotToDriverRumble>,
+        ram: Option<RobotToDriverRamUsage>,
+        disk: Option<RobotToDriverDiskUsage>,
+        cpus: Vec<CpuUsage>,
+        pdp: Option<PdpReport>,
+        pdp_power: Option<PdpPowerReport>,
+    }
+
+    impl PacketTagAcceptor for Collected {
+        fn accept_rumble(&mut self, rumble: RobotToDriverRumble) {
+            self.rumbles.push(rumble)
+        }
+        fn accept_ram_usage(&mut self, ram: RobotToDriverRamUsage) {
+            self.ram = Some(ram)
+        }
+        fn accept_disk_usage(&mut self, disk: RobotToDriverDiskUsage) {
+            self.disk = Some(disk)
+        }
+        fn accept_cpu_usage(&mut self, cpus: &[CpuUsage]) {
+            self.cpus = cpus.to_vec()
+        }
+        fn accept_can_usage(&mut self, _: RobotToDriverCanUsage) {}
+        fn accept_pdp_report(&mut self, pdp: PdpReport) {
+            self.pdp = Some(pdp)
+        }
+        fn accept_pdp_power_report(&mut self, pdp_power: PdpPowerReport) {
+            self.pdp_power = Some(pdp_power)
+        }
+    }
+
+    fn read_back(written: &[u8]) -> Collected {
+        let (_, tags) = RobotToDriverPacketReader::new(BufferReader::new(written))
+            .read_core()
+            .unwrap();
+        let mut acceptor = Collected::default();
+        tags.read_tags(&mut acceptor).unwrap();
+        acceptor
+    }
+
+    #[test]
+    pub fn rumble_round_trip() {
+        let rumbles = [
+            RobotToDriverRumble::default(),
+            RobotToDriverRumble {
+                outputs: 0b101,
+                left: u16::MAX,
+                right: 0x1234,
+            },
+        ];
+
+        let mut buf = [0u8; 64];
+        let mut bufw = SliceBufferWritter::new(&mut buf);
+        let packet = RobotToDriverstationPacket {
+            tag_comm_version: 1,
+            ..Default::default()
+        };
+        let mut writter = RobotToDriverstaionPacketWritter::new(&mut bufw, packet).unwrap();
+        for rumble in rumbles {
+            writter.rumble(rumble).unwrap();
+        }
+        let written = writter.into_buf();
+
+        let (read, _) = RobotToDriverPacketReader::new(BufferReader::new(written))
+            .read_core()
+            .unwrap();
+        assert_eq!(read, packet);
+        assert_eq!(read_back(written).rumbles, rumbles);
+    }
+
+    #[test]
+    pub fn usage_round_trip() {
+        let cpus = [
+            CpuUsage::new(12.5, 0.0, 0.0, 3.0),
+            CpuUsage::new(1.0, 2.0, 3.0, 4.0),
+        ];
+        let ram = RobotToDriverRamUsage {
+            bytes_free: 1 << 28,
+        };
+        let disk = RobotToDriverDiskUsage {
+            bytes_free: 1 << 30,
+        };
+
+        let mut buf = [0u8; 128];
+        let mut bufw = SliceBufferWritter::new(&mut buf);
+        let packet = RobotToDriverstationPacket {
+            tag_comm_version: 1,
+            ..Default::default()
+        };
+        let mut writter = RobotToDriverstaionPacketWritter::new(&mut bufw, packet).unwrap();
+        writter.disk_usage(disk).unwrap();
+        writter.cpu_usage(&cpus).unwrap();
+        writter.ram_usage(ram).unwrap();
+
+        let read = read_back(writter.into_buf());
+        assert_eq!(read.cpus, cpus);
+        assert_eq!(read.ram, Some(ram));
+        assert_eq!(read.disk, Some(disk));
+    }
+
+    #[test]
+    pub fn too_many_cpus_are_cut_off() {
+        let cpus: Vec<_> = (0..16)
+            .map(|i| CpuUsage::new(i as f32, 0.0, 0.0, 1.0))
+            .collect();
+
+        let mut buf = [0u8; 512];
+        let mut bufw = SliceBufferWritter::new(&mut buf);
+        let packet = RobotToDriverstationPacket {
+            tag_comm_version: 1,
+            ..Default::default()
+        };
+        let mut writter = RobotToDriverstaionPacketWritter::new(&mut bufw, packet).unwrap();
+        writter.cpu_usage(&cpus).unwrap();
+        writter
+            .ram_usage(RobotToDriverRamUsage { bytes_free: 1 })
+            .unwrap();
+
+        // the tag after it still reads fine so the size byte didnt wrap
+        let read = read_back(writter.into_buf());
+        assert_eq!(read.cpus, cpus[..15]);
+        assert_eq!(read.ram, Some(RobotToDriverRamUsage { bytes_free: 1 }));
+    }
+
+    #[test]
+    pub fn pdp_round_trip() {
+        let mut pdp = PdpReport::new(PdpModule::Rev);
+        pdp.currents[23] = 40.0;
+        pdp.bus_voltage = 12.5;
+        pdp.temperature = 25.0;
+        let pdp_power = PdpPowerReport {
+            period_ms: 20,
+            total_current: 40.0,
+            power: 500.0,
+            ..Default::default()
+        };
+
+        let mut buf = [0u8; 128];
+        let mut bufw = SliceBufferWritter::new(&mut buf);
+        let packet = RobotToDriverstationPacket {
+            tag_comm_version: 1,
+            ..Default::default()
+        };
+        let mut writter = RobotToDriverstaionPacketWritter::new(&mut bufw, packet).unwrap();
+        writter.pdp_report(&pdp).unwrap();
+        writter.pdp_power_report(pdp_power).unwrap();
+
+        let read = read_back(writter.into_buf());
+        let read_pdp = read.pdp.unwrap();
+        assert_eq!(read_pdp.module, pdp.module);
+        assert_eq!(read_pdp.currents, pdp.currents);
+        assert_eq!(read_pdp.bus_voltage, pdp.bus_voltage);
+        // the temperature is sent in steps of just over a degree
+        assert!((read_pdp.temperature - pdp.temperature).abs() < 0.52);
+        assert_eq!(read.pdp_power, Some(pdp_power));
+    }
 }
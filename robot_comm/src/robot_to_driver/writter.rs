@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 use util::buffer_writter::{BufferWritter, BufferWritterError, WriteToBuff};
 
 use super::{
-    CpuUsage, PdpPortReport, PdpPowerReport, RobotToDriverCanUsage, RobotToDriverDiskUsage,
+    CpuUsage, PdpPowerReport, PdpReport, RobotToDriverCanUsage, RobotToDriverDiskUsage,
     RobotToDriverRamUsage, RobotToDriverRumble, RobotToDriverstationPacket,
 };
 
@@ -29,7 +29,7 @@ impl<'a, 'b, T: BufferWritter<'a>> RobotToDriverstaionPacketWritter<'a, 'b, T> {
     pub fn rumble(&mut self, rumble: RobotToDriverRumble) -> Result<&mut Self, BufferWritterError> {
         let mut buf = self.writter.create_u8_size_guard()?;
         buf.write_u8(0x01)?;
-        buf.write_u32(rumble.no_idea)?;
+        buf.write_u32(rumble.outputs)?;
         buf.write_u16(rumble.left)?;
         buf.write_u16(rumble.right)?;
         drop(buf);
@@ -49,8 +49,12 @@ impl<'a, 'b, T: BufferWritter<'a>> RobotToDriverstaionPacketWritter<'a, 'b, T> {
     }
 
     pub fn cpu_usage(&mut self, usage: &[CpuUsage]) -> Result<&mut Self, BufferWritterError> {
+        // the size byte covers the tag, the cpu count and every cpu so only this many fit
+        const MAX_CPUS: usize = (u8::MAX as usize - 2) / std::mem::size_of::<CpuUsage>();
+        let usage = &usage[..usage.len().min(MAX_CPUS)];
         let mut buf = self.writter.create_u8_size_guard()?;
         buf.write_u8(0x05)?;
+        buf.write_u8(usage.len() as u8)?;
         for usage in usage {
             buf.write_buf_const(&usage.user)?;
             buf.write_buf_const(&usage._unknown1)?;
@@ -73,15 +77,10 @@ impl<'a, 'b, T: BufferWritter<'a>> RobotToDriverstaionPacketWritter<'a, 'b, T> {
         Ok(self)
     }
 
-    pub fn pdp_port_report(
-        &mut self,
-        report: &PdpPortReport,
-    ) -> Result<&mut Self, BufferWritterError> {
-        self.writter.write_u8(26)?; //size(we know ahead of time)
-        self.writter.write(0x08)?;
-        self.writter.write_u8(report.unknown_0)?;
-        self.writter.write_buf_const(&report.port_amp_report.0)?;
-        self.writter.write_buf_const(&report.unknown_1)?;
+    pub fn pdp_report(&mut self, report: &PdpReport) -> Result<&mut Self, BufferWritterError> {
+        self.writter.write_u8(1 + report.encoded_len() as u8)?; //size
+        self.writter.write_u8(0x08)?; //tag
+        report.write_to_buf(self.writter)?;
         self.last_sucsessful = self.writter.curr_buf_len();
         Ok(self)
     }
@@ -90,9 +89,10 @@ impl<'a, 'b, T: BufferWritter<'a>> RobotToDriverstaionPacketWritter<'a, 'b, T> {
         &mut self,
         report: PdpPowerReport,
     ) -> Result<&mut Self, BufferWritterError> {
-        self.writter.write_u8(10)?; //size(we know ahead of time)
-        self.writter.write(0x09)?;
-        self.writter.write_buf_const(&report.inner.0)?;
+        self.writter
+            .write_u8(1 + PdpPowerReport::ENCODED_LEN as u8)?; //size
+        self.writter.write_u8(0x09)?; //tag
+        report.write_to_buf(self.writter)?;
         self.last_sucsessful = self.writter.curr_buf_len();
         Ok(self)
     }
@@ -23,7 +23,7 @@ use robot_comm::{
         request_code::{DriverstationRequestCode, RobotRequestCode},
     },
     driverstation::RobotComm,
-    robot_to_driver::RobotToDriverstationPacket,
+    robot_to_driver::{RobotToDriverRumble, RobotToDriverstationPacket},
     util::buffer_writter::{BufferWritter, SliceBufferWritter, WriteToBuff},
 };
 
@@ -153,6 +153,9 @@ pub fn controller(driverstation: Arc<RobotComm>) {
     let mut gilrs = gilrs::Gilrs::new().unwrap();
     // gilrs.gamepads()
     let mut povs = [Pov::default(); 6];
+    // the outputs each effect was made for, dropping an effect stops it
+    let mut rumble: [(RobotToDriverRumble, Option<gilrs::ff::Effect>); 6] =
+        std::array::from_fn(|_| Default::default());
     loop {
         while let Some(event) = gilrs.next_event() {
             // println!("{:#?}", event);
@@ -269,6 +272,47 @@ pub fn controller(driverstation: Arc<RobotComm>) {
             });
         }
 
+        let ff_gamepads: Vec<_> = gilrs
+            .gamepads()
+            .filter(|(_, gamepad)| gamepad.is_ff_supported())
+            .map(|(id, _)| id)
+            .collect();
+        for id in ff_gamepads {
+            let index: usize = id.into();
+            let Some((last, effect)) = rumble.get_mut(index) else {
+                continue;
+            };
+            let outputs = driverstation.get_joystick_outputs(index);
+            if *last == outputs {
+                continue;
+            }
+            *last = outputs;
+            *effect = None;
+            if outputs.left == 0 && outputs.right == 0 {
+                continue;
+            }
+            // left is the big low frequency motor and right the small high frequency one
+            let res = gilrs::ff::EffectBuilder::new()
+                .add_effect(gilrs::ff::BaseEffect {
+                    kind: gilrs::ff::BaseEffectType::Strong {
+                        magnitude: outputs.left,
+                    },
+                    ..Default::default()
+                })
+                .add_effect(gilrs::ff::BaseEffect {
+                    kind: gilrs::ff::BaseEffectType::Weak {
+                        magnitude: outputs.right,
+                    },
+                    ..Default::default()
+                })
+                .gamepads(&[id])
+                .finish(&mut gilrs);
+            match res.and_then(|new| new.play().map(|_| new)) {
+                Ok(new) => *effect = Some(new),
+                Err(err) => eprintln!("Failed to play rumble on controller {index}: {err}"),
+            }
+        }
+
         std::thread::sleep(std::time::Duration::from_millis(10))
     }
 }
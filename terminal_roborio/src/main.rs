@@ -65,7 +65,7 @@ impl Log {
 fn main() -> Result<(), io::Error> {
     std::env::set_var("RUST_BACKTRACE", "1");
 
-    let driverstation = Arc::new(RoborioCom::default());
+    let driverstation = Arc::new(RoborioCom::builder().team_number(1114).build());
     let log = Arc::new(Log::default());
     let send = log.clone();
     _ = driverstation.set_error_handler(move |_com, error| send.error(format!("{:?}", error)));
@@ -97,24 +97,9 @@ fn main() -> Result<(), io::Error> {
     _ = driverstation.set_test_hook(move || {
         send.message("Hook: Test");
     });
-    RoborioCom::start_daemon(driverstation.clone());
+    let daemon = RoborioCom::start_daemon(driverstation.clone());
     // driverstation
 
-    let child = std::process::Command::new("avahi-publish-service")
-        .args(["roboRIO-1114-FRC", "_ni-rt._tcp", "1110", "\"ROBORIO\""])
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .stdin(std::process::Stdio::null())
-        .spawn()
-        .unwrap();
-    let child = KillOnDrop(child);
-    struct KillOnDrop(std::process::Child);
-    impl Drop for KillOnDrop {
-        fn drop(&mut self) {
-            _ = self.0.kill();
-        }
-    }
-
     let hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
         let mut stdout = io::stdout();
@@ -152,7 +137,7 @@ fn main() -> Result<(), io::Error> {
     execute!(stdout, EnableLineWrap)?;
     execute!(stdout, crossterm::cursor::Show)?;
 
-    drop(child);
+    _ = daemon.shutdown_and_join();
 
     match res {
         AppResult::Ok(ok) => Ok(ok),
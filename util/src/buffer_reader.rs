@@ -17,7 +17,7 @@ pub enum BufferReaderError {
         tried_index: usize,
     },
     ParseUft8Error(std::str::Utf8Error),
-    GeneralError(Box<dyn std::error::Error + 'static + Send>),
+    GeneralError(Box<dyn std::error::Error + 'static + Send + Sync>),
     BufferEmptyAssertionFailed {
         remaining: usize,
     },
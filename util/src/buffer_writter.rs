@@ -275,3 +275,48 @@ impl<'a> SliceBufferWritter<'a> {
         Self { buff, index: 0 }
     }
 }
+
+/// A [`BufferWritter`] that grows as needed, for when we dont know how big the data will be ahead of time
+#[derive(Debug, Default, Clone)]
+pub struct VecBufferWritter {
+    buff: Vec<u8>,
+}
+
+impl<'a> BufferWritter<'a> for VecBufferWritter {
+    fn reset(&mut self) {
+        self.buff.clear();
+    }
+
+    fn curr_buf(&self) -> &[u8] {
+        &self.buff
+    }
+
+    fn curr_buf_mut(&mut self) -> &mut [u8] {
+        &mut self.buff
+    }
+
+    fn write(&mut self, size: usize) -> Result<&mut [u8], BufferWritterError> {
+        let start = self.buff.len();
+        if self.buff.try_reserve(size).is_err() {
+            return Err(BufferWritterError::FailedToGrowBuffer);
+        }
+        self.buff.resize(start + size, 0);
+        Ok(&mut self.buff[start..])
+    }
+}
+
+impl VecBufferWritter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buff: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.buff
+    }
+}
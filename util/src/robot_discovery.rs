@@ -14,7 +14,7 @@ use mdns_sd::{ServiceDaemon, ServiceEvent};
 struct Daemon(ServiceDaemon);
 impl Drop for Daemon {
     fn drop(&mut self) {
-        while Err(mdns_sd::Error::Again) == self.0.shutdown() {}
+        while let Err(mdns_sd::Error::Again) = self.0.shutdown() {}
     }
 }
 
@@ -58,7 +58,7 @@ pub fn find_robot_ip(team_number: impl Into<TeamNumber>) -> Result<IpAddr, Box<d
         if let ServiceEvent::ServiceResolved(service) = event {
             if service.get_fullname().eq_ignore_ascii_case(&name) {
                 if let Some(found_ip) = service.get_addresses().iter().next() {
-                    return Ok(IpAddr::V4(*found_ip));
+                    return Ok(*found_ip);
                 }
             }
         }